@@ -1,4 +1,10 @@
 fn main() -> anyhow::Result<()> {
+    // There is no ESP-IDF configuration to propagate when testing on the host.
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("espidf") {
+        println!("cargo:rustc-check-cfg=cfg(esp32s2)");
+        return Ok(());
+    }
+
     embuild::build::CfgArgs::output_propagated("ESP_IDF")?;
     embuild::build::LinkArgs::output_propagated("ESP_IDF")
 }
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use esp_idf_sys::*;
+use log::{debug, warn};
+
+use crate::{
+    gatt_server::GattServer,
+    utilities::{BleUuid, Connection},
+};
+
+/// Represents the kind of access requested by a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// The client reads the attribute.
+    Read,
+    /// The client writes the attribute.
+    Write,
+}
+
+/// Represents a read or write request, passed to the authorization callbacks.
+#[derive(Debug, Clone, Copy)]
+pub struct AccessRequest {
+    connection: Connection,
+    attribute: BleUuid,
+    handle: u16,
+    access: Access,
+}
+
+impl AccessRequest {
+    /// Returns the connection with the client that sent the request.
+    #[must_use]
+    pub const fn connection(&self) -> Connection {
+        self.connection
+    }
+
+    /// Returns the identity address of the client that sent the request.
+    ///
+    /// Unlike the connection address, which a client using private addresses changes regularly,
+    /// the identity address of a bonded client is stable, so it can be used to authorize clients.
+    /// For clients that did not distribute their identity, this is the connection address.
+    #[must_use]
+    pub fn address(&self) -> [u8; 6] {
+        self.connection.identity_address()
+    }
+
+    /// Returns `true` if the link with the client is encrypted with the keys of a bond.
+    #[must_use]
+    pub fn is_bonded(&self) -> bool {
+        self.connection.is_bonded()
+    }
+
+    /// Returns the UUID of the requested attribute.
+    #[must_use]
+    pub const fn attribute(&self) -> BleUuid {
+        self.attribute
+    }
+
+    /// Returns the handle of the requested attribute.
+    #[must_use]
+    pub const fn handle(&self) -> u16 {
+        self.handle
+    }
+
+    /// Returns the kind of access requested by the client.
+    #[must_use]
+    pub const fn access(&self) -> Access {
+        self.access
+    }
+}
+
+/// A callback that decides whether a request is allowed.
+#[derive(Clone)]
+pub(crate) struct Authorizer(Arc<dyn Fn(&AccessRequest) -> bool + Send + Sync>);
+
+impl Authorizer {
+    pub(crate) fn new(callback: impl Fn(&AccessRequest) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Checks a request against an optional authorizer.
+    ///
+    /// Requests from unknown connections are denied.
+    pub(crate) fn check(
+        authorizer: Option<&Self>,
+        connection: Option<&Connection>,
+        attribute: BleUuid,
+        handle: u16,
+        access: Access,
+    ) -> Result<(), esp_gatt_status_t> {
+        let Some(authorizer) = authorizer else {
+            return Ok(());
+        };
+
+        let allowed = connection.is_some_and(|connection| {
+            (authorizer.0)(&AccessRequest {
+                connection: *connection,
+                attribute,
+                handle,
+                access,
+            })
+        });
+
+        if allowed {
+            Ok(())
+        } else {
+            debug!("{:?} access to {} denied.", access, attribute);
+            Err(esp_gatt_status_t_ESP_GATT_INSUF_AUTHORIZATION)
+        }
+    }
+}
+
+impl std::fmt::Debug for Authorizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "authorizer")
+    }
+}
+
+impl GattServer {
+    /// Sets a callback that decides whether a client can read or write an attribute.
+    ///
+    /// The callback applies to all the services that do not have their own authorization callback,
+    /// and must be set before starting the server. Denied requests are answered with an
+    /// "insufficient authorization" error.
+    ///
+    /// Long writes, made of several prepared writes, are authorized once, when the client executes them.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn authorize(
+        &mut self,
+        callback: impl Fn(&AccessRequest) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        if self.started {
+            warn!("Cannot set the authorization callback after the server has started.");
+            return self;
+        }
+
+        self.authorizer = Some(Authorizer::new(callback));
+        self
+    }
+
+    /// Passes the authorization callbacks down to the services and characteristics that do not have one.
+    pub(crate) fn inherit_authorizers(&self) {
+        self.profiles.iter().for_each(|profile| {
+            profile.read().unwrap().services.iter().for_each(|service| {
+                let mut service = service.write().unwrap();
+                if service.authorizer.is_none() {
+                    service.authorizer = self.authorizer.clone();
+                }
+
+                service.characteristics.iter().for_each(|characteristic| {
+                    let mut characteristic = characteristic.write().unwrap();
+                    if characteristic.authorizer.is_none() {
+                        characteristic.authorizer = service.authorizer.clone();
+                    }
+                });
+            });
+        });
+    }
+}
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use esp_idf_sys::*;
+use log::{debug, info, warn};
+
+use crate::{
+    gatt_server::{
+        cccd::{persist_cccds, purge_cccds, register_identity},
+        GattServer,
+    },
+    utilities::Connection,
+};
+
+/// Represents the outcome of a pairing or encryption procedure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticationResult {
+    address: [u8; 6],
+    key_type: u8,
+    mode: esp_ble_auth_req_t,
+    failure_reason: Option<u8>,
+}
+
+impl AuthenticationResult {
+    /// Returns the identity address of the peer.
+    ///
+    /// This is the address returned by [`Connection::identity_address`] for the peer's connection,
+    /// and the one listed by [`GattServer::bonded_devices`].
+    #[must_use]
+    pub const fn address(&self) -> [u8; 6] {
+        self.address
+    }
+
+    /// Returns the type of the key used to encrypt the link, as reported by the stack.
+    #[must_use]
+    pub const fn key_type(&self) -> u8 {
+        self.key_type
+    }
+
+    /// Returns `true` if the procedure succeeded.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.failure_reason.is_none()
+    }
+
+    /// Returns the SMP failure reason, if the procedure failed.
+    #[must_use]
+    pub const fn failure_reason(&self) -> Option<u8> {
+        self.failure_reason
+    }
+
+    /// Returns `true` if the peer has been bonded.
+    #[must_use]
+    pub const fn is_bonded(&self) -> bool {
+        self.mode as u32 & ESP_LE_AUTH_BOND != 0
+    }
+
+    /// Returns `true` if the link is protected against man-in-the-middle attacks.
+    #[must_use]
+    pub const fn is_mitm_protected(&self) -> bool {
+        self.mode as u32 & ESP_LE_AUTH_REQ_MITM != 0
+    }
+
+    /// Returns `true` if the link uses LE Secure Connections.
+    #[must_use]
+    pub const fn is_secure_connection(&self) -> bool {
+        self.mode as u32 & ESP_LE_AUTH_REQ_SC_ONLY != 0
+    }
+}
+
+impl From<esp_ble_auth_cmpl_t> for AuthenticationResult {
+    fn from(param: esp_ble_auth_cmpl_t) -> Self {
+        Self {
+            address: param.bd_addr,
+            key_type: param.key_type,
+            mode: param.auth_mode,
+            failure_reason: if param.success {
+                None
+            } else {
+                Some(param.fail_reason)
+            },
+        }
+    }
+}
+
+impl GattServer {
+    /// Returns the addresses of the bonded devices.
+    #[must_use]
+    #[allow(clippy::unused_self)]
+    pub fn bonded_devices(&self) -> Vec<[u8; 6]> {
+        Self::bonded_addresses()
+    }
+
+    /// Reads the addresses of the bonded devices from the stack.
+    #[allow(clippy::cast_sign_loss)]
+    pub(crate) fn bonded_addresses() -> Vec<[u8; 6]> {
+        let mut count = unsafe { esp_ble_get_bond_device_num() };
+        if count <= 0 {
+            return Vec::new();
+        }
+
+        let mut devices: Vec<esp_ble_bond_dev_t> = vec![Default::default(); count as usize];
+        if let Err(error) = unsafe {
+            esp!(esp_ble_get_bond_device_list(
+                &mut count,
+                devices.as_mut_ptr()
+            ))
+        } {
+            warn!("Cannot read the list of bonded devices: {}.", error);
+            return Vec::new();
+        }
+
+        devices
+            .iter()
+            .take(count.max(0) as usize)
+            .map(|device| device.bd_addr)
+            .collect()
+    }
+
+    /// Removes the bond with the given device, which has to pair again to reconnect securely.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot remove the bond.
+    #[allow(clippy::unused_self)]
+    pub fn remove_bond(&self, mut address: [u8; 6]) -> Result<(), EspError> {
+        info!("Removing bond with {:02X?}.", address);
+        unsafe { esp!(esp_ble_remove_bond_device(address.as_mut_ptr())) }
+    }
+
+    /// Removes the bonds with all the devices.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error reported by the Bluetooth stack, after trying to remove all the bonds.
+    pub fn clear_bonds(&self) -> Result<(), EspError> {
+        self.bonded_devices()
+            .into_iter()
+            .map(|address| self.remove_bond(address))
+            .fold(Ok(()), Result::and)
+    }
+
+    /// Sets a callback that is called when a pairing or encryption procedure completes, successfully or not.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_authentication_complete(
+        &mut self,
+        callback: impl Fn(AuthenticationResult) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.authentication_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Handles the completion of a pairing or encryption procedure.
+    pub(crate) fn on_authentication_complete_event(&mut self, param: esp_ble_auth_cmpl_t) {
+        let result = AuthenticationResult::from(param);
+
+        if let Some(reason) = result.failure_reason() {
+            warn!(
+                "Authentication with {:02X?} failed with reason 0x{:02X}.",
+                result.address(),
+                reason
+            );
+        } else {
+            info!(
+                "Authentication with {:02X?} complete (bonded: {}, MITM: {}, secure connection: {}).",
+                result.address(),
+                result.is_bonded(),
+                result.is_mitm_protected(),
+                result.is_secure_connection()
+            );
+        }
+
+        let connection = self
+            .connection_of_peer(param.bd_addr)
+            .map(|mut connection| {
+                connection.authentication_mode = result.is_success().then_some(param.auth_mode);
+
+                if result.is_success() && connection.remote_bda != param.bd_addr {
+                    connection.identity_bda = Some(param.bd_addr);
+                    register_identity(connection.remote_bda, param.bd_addr);
+                }
+
+                self.active_connections.replace(connection);
+                connection
+            });
+
+        if connection.is_none() {
+            warn!(
+                "Cannot find the connection authenticated with {:02X?}.",
+                param.bd_addr
+            );
+        }
+
+        // Subscriptions made before bonding must now be persisted.
+        if result.is_success() && result.is_bonded() {
+            match connection {
+                Some(connection) => {
+                    persist_cccds(connection.remote_bda, connection.identity_address());
+                }
+                None => persist_cccds(param.bd_addr, param.bd_addr),
+            }
+        }
+
+        if let Some(connection) = connection {
+            self.enforce_encryption(connection.id);
+        }
+
+        if let Some(callback) = &self.authentication_callback {
+            callback(result);
+        }
+    }
+
+    /// Handles a key distributed by a peer while bonding.
+    ///
+    /// The identity address is recorded on the peer's connection, so that the
+    /// events that report it can be matched to the connection.
+    pub(crate) fn on_key_event(&mut self, param: esp_ble_key_t) {
+        if u32::from(param.key_type) != ESP_LE_KEY_PID {
+            return;
+        }
+
+        let identity = unsafe { param.p_key_value.pid_key }.static_addr;
+
+        if let Some(mut connection) = self.connection_of_peer(param.bd_addr) {
+            debug!(
+                "{} distributed the identity address {:02X?}.",
+                connection, identity
+            );
+            connection.identity_bda = Some(identity);
+            register_identity(connection.remote_bda, identity);
+            self.active_connections.replace(connection);
+        }
+    }
+
+    /// Returns the connection of a peer reported by a security event.
+    ///
+    /// Bluedroid reports peers that distributed their identity with their identity address,
+    /// which differs from the connection address if they connected with a resolvable private address.
+    /// If no connection has the address, the identity has not been recorded yet, so the only
+    /// connection that is not encrypted is assumed.
+    fn connection_of_peer(&self, address: [u8; 6]) -> Option<Connection> {
+        if let Some(connection) = self
+            .active_connections
+            .iter()
+            .find(|connection| connection.has_address(address))
+        {
+            return Some(*connection);
+        }
+
+        let mut unencrypted = self.active_connections.iter().filter(|connection| {
+            connection.authentication_mode.is_none() && connection.identity_bda.is_none()
+        });
+
+        match (unencrypted.next(), unencrypted.next()) {
+            (Some(connection), None) => Some(*connection),
+            _ => None,
+        }
+    }
+
+    /// Handles the removal of a bond.
+    #[allow(clippy::unused_self)]
+    pub(crate) fn on_bond_removed(
+        &mut self,
+        param: esp_ble_gap_cb_param_t_ble_remove_bond_dev_cmpl_evt_param,
+    ) {
+        if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            debug!("Bond with {:02X?} removed.", param.bd_addr);
+            purge_cccds(param.bd_addr);
+        } else {
+            warn!(
+                "Cannot remove bond with {:02X?}: status {}.",
+                param.bd_addr, param.status
+            );
+        }
+    }
+}
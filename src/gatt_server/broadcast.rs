@@ -0,0 +1,163 @@
+use std::{
+    sync::{Condvar, Mutex, Once},
+    time::Duration,
+};
+
+use log::{debug, warn};
+
+use crate::{
+    gatt_server::{GattServer, GLOBAL_GATT_SERVER},
+    utilities::BleUuid,
+};
+
+/// The SCCDs of the characteristics that can be broadcast: the handle of the SCCD,
+/// the handle of its characteristic and whether a client enabled broadcasting.
+///
+/// The Server Characteristic Configuration is shared by all clients.
+static SCCDS: Mutex<Vec<(u16, u16, bool)>> = Mutex::new(Vec::new());
+
+/// Whether the broadcast values changed since the advertisement was last updated.
+static REFRESH_PENDING: Mutex<bool> = Mutex::new(false);
+
+/// Notified when [`REFRESH_PENDING`] is set.
+static REFRESH_REQUESTED: Condvar = Condvar::new();
+
+/// Starts the thread that updates the advertisement.
+static REFRESH_WORKER: Once = Once::new();
+
+/// The minimum time between two updates of the advertisement.
+///
+/// Values that change faster are coalesced, and only the latest one is advertised.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The bit of the SCCD value that enables broadcasting.
+const BROADCAST_BIT: u8 = 0b0000_0001;
+
+/// Associates the attribute handle of an SCCD with the one of its characteristic.
+pub(crate) fn register_sccd(sccd_handle: u16, characteristic_handle: u16) {
+    debug!(
+        "SCCD at handle 0x{:04x} configures characteristic at handle 0x{:04x}.",
+        sccd_handle, characteristic_handle
+    );
+
+    let mut sccds = SCCDS.lock().unwrap();
+    sccds.retain(|(existing, _, _)| *existing != sccd_handle);
+    sccds.push((sccd_handle, characteristic_handle, false));
+}
+
+/// Returns the value of an SCCD.
+pub(crate) fn read_sccd(sccd_handle: u16) -> [u8; 2] {
+    let enabled = SCCDS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(existing, _, enabled)| *existing == sccd_handle && *enabled);
+
+    [if enabled { BROADCAST_BIT } else { 0 }, 0]
+}
+
+/// Sets the value of an SCCD, and updates the advertisement if broadcasting changed.
+pub(crate) fn write_sccd(sccd_handle: u16, value: &[u8]) {
+    let enabled = value
+        .first()
+        .is_some_and(|flags| flags & BROADCAST_BIT != 0);
+
+    let changed = SCCDS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .find(|(existing, _, _)| *existing == sccd_handle)
+        .is_some_and(|(_, _, current)| std::mem::replace(current, enabled) != enabled);
+
+    if changed {
+        debug!(
+            "Broadcasting {} by SCCD at handle 0x{:04x}.",
+            if enabled { "enabled" } else { "disabled" },
+            sccd_handle
+        );
+        refresh_broadcasts();
+    }
+}
+
+/// Returns `true` if a client enabled broadcasting for the characteristic at the given handle.
+pub(crate) fn is_broadcasting(characteristic_handle: u16) -> bool {
+    SCCDS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(_, existing, enabled)| *existing == characteristic_handle && *enabled)
+}
+
+/// Updates the broadcast values in the advertisement.
+///
+/// The update happens in a worker thread, which coalesces the updates requested in a burst.
+pub(crate) fn refresh_broadcasts() {
+    *REFRESH_PENDING.lock().unwrap() = true;
+    REFRESH_REQUESTED.notify_one();
+
+    // The lock is held while handling events, so the update must happen in another thread.
+    REFRESH_WORKER.call_once(|| {
+        std::thread::spawn(|| loop {
+            let mut pending = REFRESH_REQUESTED
+                .wait_while(REFRESH_PENDING.lock().unwrap(), |pending| !*pending)
+                .unwrap();
+            *pending = false;
+            drop(pending);
+
+            {
+                let mut server = GLOBAL_GATT_SERVER
+                    .lock()
+                    .expect("Cannot lock global GATT server.");
+
+                if server.advertisement_configured {
+                    server.configure_advertisement_data();
+                }
+            }
+
+            std::thread::sleep(MIN_REFRESH_INTERVAL);
+        });
+    });
+}
+
+impl GattServer {
+    /// Returns the service data of the characteristics that are being broadcast.
+    ///
+    /// Each value is advertised as a "Service Data" AD structure of its service.
+    pub(crate) fn broadcast_service_data(&self) -> Vec<(BleUuid, Vec<u8>)> {
+        let handles: Vec<u16> = SCCDS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(_, handle, _)| *handle)
+            .collect();
+
+        if handles.is_empty() {
+            return Vec::new();
+        }
+
+        let mut service_data = Vec::new();
+        for profile in &self.profiles {
+            for service in &profile.read().unwrap().services {
+                let service = service.read().unwrap();
+
+                for characteristic in &service.characteristics {
+                    let characteristic = characteristic.read().unwrap();
+
+                    if characteristic
+                        .attribute_handle
+                        .is_some_and(|handle| handles.contains(&handle))
+                    {
+                        service_data.push((service.uuid, characteristic.internal_value.clone()));
+                    }
+                }
+            }
+        }
+
+        if service_data.len() < handles.len() {
+            warn!("Cannot find some of the characteristics to broadcast.");
+        }
+
+        service_data
+    }
+}
@@ -0,0 +1,236 @@
+use std::sync::Mutex;
+
+use log::{debug, warn};
+
+use crate::gatt_server::GattServer;
+
+/// The hash of the attribute path of each CCCD, with its attribute handle.
+///
+/// Handles change when the attribute table changes, so they cannot be used as storage keys.
+static CCCD_PATHS: Mutex<Vec<(u16, u32)>> = Mutex::new(Vec::new());
+
+/// The subscriptions of the clients that are not bonded, with their address and the path hash.
+///
+/// These are lost when the client disconnects, as required by the specification.
+static VOLATILE_CCCDS: Mutex<Vec<([u8; 6], u32, [u8; 2])>> = Mutex::new(Vec::new());
+
+/// The identity address of the clients that distributed one, with their connection address.
+///
+/// Subscriptions are stored under the identity address, which does not change across connections,
+/// while GATT requests report the connection address.
+static IDENTITY_ADDRESSES: Mutex<Vec<([u8; 6], [u8; 6])>> = Mutex::new(Vec::new());
+
+/// The length of a stored subscription: four bytes of path hash and two bytes of CCCD value.
+const ENTRY_LENGTH: usize = 6;
+
+/// The maximum number of subscriptions stored for a single client.
+const MAX_ENTRIES: usize = 64;
+
+/// Returns the 32-bit FNV-1a hash of an attribute path.
+fn path_hash(path: &str) -> u32 {
+    path.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Returns the NVS key under which the subscriptions of a bonded client are stored.
+fn storage_key(address: [u8; 6]) -> String {
+    format!(
+        "c{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        address[0], address[1], address[2], address[3], address[4], address[5]
+    )
+}
+
+/// Associates the attribute handle of a CCCD with its stable path.
+pub(crate) fn register_cccd_path(handle: u16, path: &str) {
+    debug!("CCCD at handle 0x{:04x} has path {}.", handle, path);
+
+    let hash = path_hash(path);
+    let mut paths = CCCD_PATHS.lock().unwrap();
+    paths.retain(|(existing, _)| *existing != handle);
+    paths.push((handle, hash));
+}
+
+fn path_hash_of(handle: u16) -> Option<u32> {
+    let hash = CCCD_PATHS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(existing, _)| *existing == handle)
+        .map(|(_, hash)| *hash);
+
+    if hash.is_none() {
+        warn!(
+            "No path registered for the CCCD at handle 0x{:04x}.",
+            handle
+        );
+    }
+
+    hash
+}
+
+fn is_bonded(identity: [u8; 6]) -> bool {
+    GattServer::bonded_addresses().contains(&identity)
+}
+
+/// Returns the identity address of a client, given its connection address.
+fn identity_of(address: [u8; 6]) -> [u8; 6] {
+    IDENTITY_ADDRESSES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(connection, _)| *connection == address)
+        .map_or(address, |(_, identity)| *identity)
+}
+
+/// Records the identity address of a client, under which its subscriptions are stored.
+pub(crate) fn register_identity(address: [u8; 6], identity: [u8; 6]) {
+    let mut identities = IDENTITY_ADDRESSES.lock().unwrap();
+    identities.retain(|(connection, _)| *connection != address);
+    identities.push((address, identity));
+}
+
+/// Reads the subscriptions stored for a bonded client.
+fn load(identity: [u8; 6]) -> Vec<(u32, [u8; 2])> {
+    match GattServer::with_storage(|storage| storage.get(&storage_key(identity))) {
+        Ok(Some(value)) => value
+            .chunks_exact(ENTRY_LENGTH)
+            .map(|entry| {
+                (
+                    u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]),
+                    [entry[4], entry[5]],
+                )
+            })
+            .collect(),
+        Ok(None) => Vec::new(),
+        Err(error) => {
+            warn!("Cannot read the CCCDs of {:02X?}: {}.", identity, error);
+            Vec::new()
+        }
+    }
+}
+
+/// Stores the subscriptions of a bonded client, removing the key if there are none.
+fn store(identity: [u8; 6], entries: &[(u32, [u8; 2])]) {
+    let key = storage_key(identity);
+
+    let result = GattServer::with_storage(|storage| {
+        if entries.is_empty() {
+            storage.remove(&key)
+        } else {
+            let buf: Vec<u8> = entries
+                .iter()
+                .take(MAX_ENTRIES)
+                .flat_map(|(hash, value)| hash.to_le_bytes().into_iter().chain(*value))
+                .collect();
+
+            storage.set(&key, &buf)
+        }
+    });
+
+    if let Err(error) = result {
+        warn!("Cannot store the CCCDs of {:02X?}: {}.", identity, error);
+    }
+}
+
+/// Returns the value of a CCCD for a client, given its connection address.
+pub(crate) fn read_cccd(address: [u8; 6], handle: u16) -> [u8; 2] {
+    let Some(hash) = path_hash_of(handle) else {
+        return [0, 0];
+    };
+
+    let identity = identity_of(address);
+    let value = if is_bonded(identity) {
+        load(identity)
+            .into_iter()
+            .find(|(existing, _)| *existing == hash)
+            .map(|(_, value)| value)
+    } else {
+        VOLATILE_CCCDS
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(peer, existing, _)| *peer == address && *existing == hash)
+            .map(|(_, _, value)| *value)
+    };
+
+    debug!(
+        "Read CCCD value {:02X?} at handle 0x{:04x} for {:02X?}.",
+        value, handle, address
+    );
+
+    value.unwrap_or([0, 0])
+}
+
+/// Sets the value of a CCCD for a client, given its connection address.
+///
+/// The value is persisted only if the client is bonded.
+pub(crate) fn write_cccd(address: [u8; 6], handle: u16, value: [u8; 2]) {
+    let Some(hash) = path_hash_of(handle) else {
+        return;
+    };
+
+    debug!(
+        "Write CCCD value {:02X?} at handle 0x{:04x} for {:02X?}.",
+        value, handle, address
+    );
+
+    let identity = identity_of(address);
+    if is_bonded(identity) {
+        let mut entries = load(identity);
+        entries.retain(|(existing, _)| *existing != hash);
+        if value != [0, 0] {
+            entries.push((hash, value));
+        }
+        store(identity, &entries);
+    } else {
+        let mut volatile = VOLATILE_CCCDS.lock().unwrap();
+        volatile.retain(|(peer, existing, _)| !(*peer == address && *existing == hash));
+        if value != [0, 0] {
+            volatile.push((address, hash, value));
+        }
+    }
+}
+
+/// Moves the subscriptions made before bonding to the persistent storage.
+///
+/// The subscriptions were made with the connection address, and are stored under the identity address.
+pub(crate) fn persist_cccds(address: [u8; 6], identity: [u8; 6]) {
+    let mut volatile = VOLATILE_CCCDS.lock().unwrap();
+    if !volatile.iter().any(|(peer, _, _)| *peer == address) {
+        return;
+    }
+
+    let mut entries = load(identity);
+    volatile.retain(|(peer, hash, value)| {
+        if *peer != address {
+            return true;
+        }
+
+        entries.retain(|(existing, _)| existing != hash);
+        entries.push((*hash, *value));
+        false
+    });
+
+    debug!("Persisting the CCCDs of {:02X?}.", identity);
+    store(identity, &entries);
+}
+
+/// Drops the subscriptions of a client that is not bonded, and its identity, when it disconnects.
+pub(crate) fn forget_cccds(address: [u8; 6]) {
+    VOLATILE_CCCDS
+        .lock()
+        .unwrap()
+        .retain(|(peer, _, _)| *peer != address);
+
+    IDENTITY_ADDRESSES
+        .lock()
+        .unwrap()
+        .retain(|(connection, _)| *connection != address);
+}
+
+/// Removes the stored subscriptions of a client whose bond has been removed, given its identity address.
+pub(crate) fn purge_cccds(identity: [u8; 6]) {
+    debug!("Purging the CCCDs of {:02X?}.", identity);
+    store(identity, &[]);
+}
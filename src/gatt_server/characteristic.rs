@@ -1,13 +1,19 @@
 use crate::{
+    gatt_server::authorization::{AccessRequest, Authorizer},
+    gatt_server::broadcast::{is_broadcasting, refresh_broadcasts},
     gatt_server::descriptor::Descriptor,
+    gatt_server::persistence::Persistence,
     leaky_box_raw,
-    utilities::{AttributeControl, AttributePermissions, BleUuid, CharacteristicProperties},
+    utilities::{
+        formats::{Format, Unit},
+        AttributeControl, AttributePermissions, BleUuid, CharacteristicProperties,
+    },
 };
 
 use esp_idf_sys::{
     esp_attr_control_t, esp_attr_value_t, esp_ble_gatts_add_char,
     esp_ble_gatts_cb_param_t_gatts_read_evt_param, esp_ble_gatts_cb_param_t_gatts_write_evt_param,
-    esp_ble_gatts_set_attr_value, esp_nofail,
+    esp_ble_gatts_set_attr_value, esp_gatt_status_t, esp_nofail, ESP_GATT_RSP_BY_APP,
 };
 use log::{debug, warn};
 use std::{
@@ -16,6 +22,7 @@ use std::{
 };
 
 type WriteCallback = dyn Fn(Vec<u8>, esp_ble_gatts_cb_param_t_gatts_write_evt_param) + Send + Sync;
+type WriteValidator = dyn Fn(&[u8]) -> Result<(), esp_gatt_status_t> + Send + Sync;
 
 /// Represents a GATT characteristic.
 #[derive(Clone)]
@@ -33,7 +40,7 @@ pub struct Characteristic {
     /// The handle of the containing service.
     service_handle: Option<u16>,
     /// The access permissions for this characteristic.
-    permissions: AttributePermissions,
+    pub(crate) permissions: AttributePermissions,
     /// The properties that are announced for this characteristic.
     pub(crate) properties: CharacteristicProperties,
     /// The way this characteristic is read.
@@ -41,9 +48,15 @@ pub struct Characteristic {
     /// A buffer for keeping in memory the actual value of this characteristic.
     pub(crate) internal_value: Vec<u8>,
     /// The maximum length of the characteristic value.
-    max_value_length: Option<u16>,
+    pub(crate) max_value_length: Option<u16>,
     /// A copy of the `control` property, in the `esp_attr_control_t` type, passed directly to the Bluetooth stack.
     internal_control: esp_attr_control_t,
+    /// The callback that decides whether a request to this characteristic is allowed.
+    pub(crate) authorizer: Option<Authorizer>,
+    /// Where the value written by clients is persisted.
+    pub(crate) persistence: Option<Persistence>,
+    /// The functions that check the values written by clients before accepting them.
+    pub(crate) write_validators: Vec<Arc<WriteValidator>>,
 }
 
 impl Characteristic {
@@ -63,6 +76,9 @@ impl Characteristic {
             control: AttributeControl::AutomaticResponse(vec![0]),
             internal_control: AttributeControl::AutomaticResponse(vec![0]).into(),
             max_value_length: None,
+            authorizer: None,
+            persistence: None,
+            write_validators: Vec::new(),
         }
     }
 
@@ -132,6 +148,8 @@ impl Characteristic {
     ///
     /// The callback receives a `Vec<u8>` with the written value.
     /// It is up to the library user to decode the data into a meaningful format.
+    /// Values that do not meet the constraints set with [`Characteristic::fixed_length`],
+    /// [`Characteristic::valid_range`] and similar methods are rejected before reaching the callback.
     pub fn on_write(
         &mut self,
         callback: impl Fn(Vec<u8>, esp_ble_gatts_cb_param_t_gatts_write_evt_param)
@@ -139,8 +157,10 @@ impl Characteristic {
             + Sync
             + 'static,
     ) -> &mut Self {
-        if !((self.properties.write || self.properties.write_without_response)
-            && self.permissions.write_access)
+        if !((self.properties.write
+            || self.properties.write_without_response
+            || self.properties.authenticated_signed_writes)
+            && self.permissions.writable())
         {
             warn!(
                 "Characteristic {} does not have write permissions. Ignoring write callback.",
@@ -154,6 +174,25 @@ impl Characteristic {
         self
     }
 
+    /// Sets a callback that decides whether a client can read or write this characteristic and its descriptors.
+    ///
+    /// The callback overrides the ones set on the [`Service`] and on the [`GattServer`].
+    /// Denied requests are answered with an "insufficient authorization" error.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    ///
+    /// [`Service`]: crate::gatt_server::Service
+    /// [`GattServer`]: crate::gatt_server::GattServer
+    pub fn authorize(
+        &mut self,
+        callback: impl Fn(&AccessRequest) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.authorizer = Some(Authorizer::new(callback));
+        self
+    }
+
     /// Creates a new "User description" descriptor for this characteristic
     /// that contains the name of the characteristic.
     pub fn show_name(&mut self) -> &mut Self {
@@ -168,6 +207,25 @@ impl Characteristic {
         self
     }
 
+    /// Creates a new "Presentation Format" descriptor for this characteristic.
+    ///
+    /// Characteristic values made of several fields need one presentation format per field, in order.
+    /// In that case, an "Aggregate Format" descriptor is added when the characteristic is registered.
+    /// See [`Descriptor::presentation_format`] for the meaning of the parameters.
+    pub fn presentation(
+        &mut self,
+        format: Format,
+        exponent: i8,
+        unit: Unit,
+        namespace: u8,
+        description: u16,
+    ) -> &mut Self {
+        self.descriptor(
+            &Descriptor::presentation_format(format, exponent, unit, namespace, description)
+                .build(),
+        )
+    }
+
     /// Sets the value of this [`Characteristic`].
     ///
     /// Sends notifications and indications to all subscribed clients.
@@ -220,6 +278,10 @@ impl Characteristic {
                     self.internal_value.as_slice().as_ptr()
                 ));
             }
+
+            if is_broadcasting(handle) {
+                refresh_broadcasts();
+            }
         }
 
         self
@@ -241,6 +303,7 @@ impl Characteristic {
             self, service_handle
         );
         self.service_handle = Some(service_handle);
+        self.load_persisted_value();
 
         #[allow(clippy::manual_assert)]
         if let AttributeControl::AutomaticResponse(_) = self.control {
@@ -249,6 +312,41 @@ impl Characteristic {
             }
         }
 
+        self.check_consistency();
+
+        // The library must see every request that it has to check.
+        #[allow(clippy::cast_possible_truncation)]
+        if self.responds_by_app() {
+            self.internal_control.auto_rsp = ESP_GATT_RSP_BY_APP as u8;
+        }
+
+        // Declare the extended properties.
+        if self.properties.extended_properties && !self.has_descriptor(0x2900) {
+            self.descriptor(
+                &Descriptor::extended_properties(
+                    self.properties.reliable_write,
+                    self.properties.writable_auxiliaries,
+                )
+                .build(),
+            );
+        }
+
+        // Let the clients enable broadcasting.
+        if self.properties.broadcast && !self.has_descriptor(0x2903) {
+            self.descriptor(&Descriptor::sccd().build());
+        }
+
+        // List the presentation formats of values made of several fields.
+        let presentation_formats = self
+            .descriptors
+            .iter()
+            .filter(|descriptor| descriptor.read().unwrap().uuid == BleUuid::Uuid16(0x2904))
+            .count();
+
+        if presentation_formats > 1 && !self.has_descriptor(0x2905) {
+            self.descriptor(&Descriptor::aggregate_format(presentation_formats).build());
+        }
+
         // Register a CCCD if needed.
         if self.properties.notify || self.properties.indicate {
             self.descriptor(&Descriptor::cccd().build());
@@ -288,15 +386,93 @@ impl Characteristic {
     pub(crate) fn register_descriptors(&mut self) {
         debug!("Registering {}'s descriptors.", &self);
         self.descriptors.iter_mut().for_each(|descriptor| {
-            descriptor
-                .write()
-                .unwrap()
-                .register_self(self.service_handle.expect(
-                    "Cannot register a descriptor to a characteristic without a service handle.",
-                ));
+            let mut descriptor = descriptor.write().unwrap();
+
+            // Descriptors share the authorization callback of their characteristic.
+            if descriptor.authorizer.is_none() {
+                descriptor.authorizer = self.authorizer.clone();
+            }
+
+            descriptor.register_self(self.service_handle.expect(
+                "Cannot register a descriptor to a characteristic without a service handle.",
+            ));
         });
     }
 
+    /// Returns `true` if this [`Characteristic`] has a descriptor with the given 16-bit UUID.
+    fn has_descriptor(&self, uuid: u16) -> bool {
+        self.descriptors
+            .iter()
+            .any(|descriptor| descriptor.read().unwrap().uuid == BleUuid::Uuid16(uuid))
+    }
+
+    /// Warns about properties that do not match the access permissions.
+    fn check_consistency(&self) {
+        if self.properties.read && !self.permissions.read_access {
+            warn!(
+                "Characteristic {} has the read property, but cannot be read.",
+                self
+            );
+        }
+
+        if (self.properties.write || self.properties.write_without_response)
+            && !self.permissions.write_access
+        {
+            warn!(
+                "Characteristic {} has the write property, but cannot be written.",
+                self
+            );
+        }
+
+        if self.properties.authenticated_signed_writes && !self.permissions.signed_writes {
+            warn!(
+                "Characteristic {} has the authenticated signed writes property, but does not allow signed writes.",
+                self
+            );
+        }
+
+        if self.permissions.signed_writes && !self.properties.authenticated_signed_writes {
+            warn!(
+                "Characteristic {} allows signed writes, but does not have the authenticated signed writes property.",
+                self
+            );
+        }
+    }
+
+    /// Returns `true` if the requests to this [`Characteristic`] are answered by the library.
+    pub(crate) fn responds_by_app(&self) -> bool {
+        matches!(self.control, AttributeControl::ResponseByApp(_))
+            || self.permissions.requires_runtime_checks()
+            || self.authorizer.is_some()
+            || !self.write_validators.is_empty()
+            // Long values must be assembled by the library, to be persisted once complete.
+            || self.persistence.is_some()
+    }
+
+    /// Checks a value written by a client against the constraints of this [`Characteristic`].
+    pub(crate) fn validate(&self, value: &[u8]) -> Result<(), esp_gatt_status_t> {
+        self.write_validators
+            .iter()
+            .try_for_each(|validator| validator(value))
+    }
+
+    /// Returns the current value of this [`Characteristic`], calling the read callback if there is one.
+    pub(crate) fn read_value(
+        &self,
+        param: esp_ble_gatts_cb_param_t_gatts_read_evt_param,
+    ) -> Vec<u8> {
+        match &self.control {
+            AttributeControl::ResponseByApp(callback) => callback(param),
+            AttributeControl::AutomaticResponse(_) => self.internal_value.clone(),
+        }
+    }
+
+    /// Returns the maximum length of the value of this [`Characteristic`].
+    pub(crate) fn value_capacity(&self) -> usize {
+        self.max_value_length
+            .map_or(self.internal_value.len(), usize::from)
+    }
+
     pub(crate) fn get_cccd_status(
         &self,
         param: esp_ble_gatts_cb_param_t_gatts_read_evt_param,
@@ -349,6 +525,9 @@ impl std::fmt::Debug for Characteristic {
             .field("internal_value", &self.internal_value)
             .field("max_value_length", &self.max_value_length)
             .field("internal_control", &self.internal_control)
+            .field("authorizer", &self.authorizer)
+            .field("persistence", &self.persistence)
+            .field("write_validators", &self.write_validators.len())
             .finish()
     }
 }
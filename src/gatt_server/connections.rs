@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use esp_idf_sys::*;
+use log::{debug, info, warn};
+
+#[cfg(esp_idf_bt_ble_50_features_supported)]
+use crate::utilities::Phy;
+use crate::{
+    gatt_server::GattServer,
+    utilities::{
+        Connection, ConnectionParameters, PENDING_DATA_LENGTH_REQUEST, RSSI_READINGS,
+        RSSI_READING_AVAILABLE,
+    },
+};
+
+impl GattServer {
+    /// Returns the active connections.
+    #[must_use]
+    pub fn connections(&self) -> Vec<Connection> {
+        self.active_connections.iter().copied().collect()
+    }
+
+    /// Sets a callback that is called when the parameters of a connection change,
+    /// or when a parameter update request is rejected.
+    ///
+    /// The callback receives the connection and either the new parameters or the status reported by the stack.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_connection_parameters_change(
+        &mut self,
+        callback: impl Fn(Connection, Result<ConnectionParameters, esp_bt_status_t>)
+            + Send
+            + Sync
+            + 'static,
+    ) -> &mut Self {
+        self.connection_parameters_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Handles the completion of a connection parameters update.
+    pub(crate) fn on_connection_parameters_update(
+        &mut self,
+        param: esp_ble_gap_cb_param_t_ble_update_conn_params_evt_param,
+    ) {
+        let Some(mut connection) = self
+            .active_connections
+            .iter()
+            .find(|connection| connection.remote_bda == param.bda)
+            .copied()
+        else {
+            warn!(
+                "Connection parameters updated for unknown device {:02X?}.",
+                param.bda
+            );
+            return;
+        };
+
+        let result = if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            let parameters =
+                ConnectionParameters::from_raw(param.conn_int, param.latency, param.timeout);
+            info!(
+                "Connection parameters of {} updated: {:?}.",
+                connection, parameters
+            );
+
+            connection.parameters = Some(parameters);
+            self.active_connections.replace(connection);
+
+            Ok(parameters)
+        } else {
+            warn!(
+                "Connection parameters update of {} failed with status {}.",
+                connection, param.status
+            );
+
+            Err(param.status)
+        };
+
+        if let Some(callback) = &self.connection_parameters_callback {
+            callback(connection, result);
+        }
+    }
+
+    /// Handles the completion of a data length request.
+    ///
+    /// The changes requested by the remote devices cannot be attributed to a connection,
+    /// so they are ignored when no request is pending.
+    pub(crate) fn on_data_length_update(
+        &mut self,
+        param: esp_ble_gap_cb_param_t_ble_pkt_data_length_cmpl_evt_param,
+    ) {
+        let Some(address) = PENDING_DATA_LENGTH_REQUEST.lock().unwrap().take() else {
+            debug!("Data length updated without a pending request. Ignoring update.");
+            return;
+        };
+
+        let Some(mut connection) = self
+            .active_connections
+            .iter()
+            .find(|connection| connection.remote_bda == address)
+            .copied()
+        else {
+            debug!(
+                "Data length updated for disconnected device {:02X?}.",
+                address
+            );
+            return;
+        };
+
+        if param.status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "Data length update of {} failed with status {}.",
+                connection, param.status
+            );
+            return;
+        }
+
+        info!(
+            "Data length of {} updated: {} bytes (TX), {} bytes (RX).",
+            connection, param.params.tx_len, param.params.rx_len
+        );
+
+        connection.max_tx_octets = param.params.tx_len;
+        connection.max_rx_octets = param.params.rx_len;
+        self.active_connections.replace(connection);
+    }
+
+    /// Handles the completion of an RSSI reading, waking up the threads waiting for it.
+    #[allow(clippy::unused_self)]
+    pub(crate) fn on_rssi_read(&self, param: esp_ble_gap_cb_param_t_ble_read_rssi_cmpl_evt_param) {
+        let result = if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            debug!("RSSI of {:02X?}: {} dBm.", param.remote_addr, param.rssi);
+            Ok(param.rssi)
+        } else {
+            debug!(
+                "RSSI reading of {:02X?} failed with status {}.",
+                param.remote_addr, param.status
+            );
+            Err(param.status)
+        };
+
+        RSSI_READINGS
+            .lock()
+            .unwrap()
+            .push((param.remote_addr, result));
+        RSSI_READING_AVAILABLE.notify_all();
+    }
+}
+
+#[cfg(esp_idf_bt_ble_50_features_supported)]
+impl GattServer {
+    /// Handles the completion of a PHY update.
+    pub(crate) fn on_phy_update(
+        &mut self,
+        param: esp_ble_gap_cb_param_t_ble_phy_update_cmpl_evt_param,
+    ) {
+        let Some(mut connection) = self
+            .active_connections
+            .iter()
+            .find(|connection| connection.remote_bda == param.bda)
+            .copied()
+        else {
+            warn!("PHY updated for unknown device {:02X?}.", param.bda);
+            return;
+        };
+
+        if param.status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "PHY update of {} failed with status {}.",
+                connection, param.status
+            );
+            return;
+        }
+
+        let (Some(tx_phy), Some(rx_phy)) =
+            (Phy::from_hci(param.tx_phy), Phy::from_hci(param.rx_phy))
+        else {
+            warn!(
+                "Unknown PHYs {} (TX), {} (RX) reported for {}.",
+                param.tx_phy, param.rx_phy, connection
+            );
+            return;
+        };
+
+        info!(
+            "PHY of {} updated: {} (TX), {} (RX).",
+            connection, tx_phy, rx_phy
+        );
+
+        connection.tx_phy = tx_phy;
+        connection.rx_phy = rx_phy;
+        self.active_connections.replace(connection);
+    }
+}
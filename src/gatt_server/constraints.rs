@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use esp_idf_sys::{
+    esp_gatt_status_t, esp_gatt_status_t_ESP_GATT_ILLEGAL_PARAMETER,
+    esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN, esp_gatt_status_t_ESP_GATT_OUT_OF_RANGE,
+};
+use log::warn;
+
+use crate::{
+    gatt_server::{Characteristic, Descriptor},
+    utilities::GattValue,
+};
+
+impl Characteristic {
+    /// Adds a check on the values written by clients, run before the write callback.
+    ///
+    /// Long values, written in several parts, are checked once, when the client executes the writes.
+    pub(crate) fn validate_writes(
+        &mut self,
+        validator: impl Fn(&[u8]) -> Result<(), esp_gatt_status_t> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.write_validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Accepts only the written values that are exactly `length` bytes long.
+    ///
+    /// Other values are rejected with an "invalid attribute length" error.
+    /// If no maximum length is set, it is set to `length`.
+    pub fn fixed_length(&mut self, length: u16) -> &mut Self {
+        self.length_range(length, length)
+    }
+
+    /// Accepts only the written values whose length is between `minimum` and `maximum` bytes, inclusive.
+    ///
+    /// Other values are rejected with an "invalid attribute length" error.
+    /// The length of a long value is checked once all its parts have been written.
+    /// If no maximum length is set, it is set to `maximum`.
+    pub fn length_range(&mut self, minimum: u16, maximum: u16) -> &mut Self {
+        if minimum > maximum {
+            warn!(
+                "Invalid length range {}..={} for characteristic {}. Ignoring it.",
+                minimum, maximum, self
+            );
+            return self;
+        }
+
+        if self.max_value_length.is_none() {
+            self.max_value_length(maximum);
+        }
+
+        let range = usize::from(minimum)..=usize::from(maximum);
+        self.validate_writes(move |value| {
+            if range.contains(&value.len()) {
+                Ok(())
+            } else {
+                Err(esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN)
+            }
+        })
+    }
+
+    /// Accepts only the written values of type `T` between `minimum` and `maximum`, inclusive.
+    ///
+    /// Values that cannot be decoded are rejected with an "invalid attribute length" error,
+    /// the ones out of the range with an "out of range" error.
+    /// A "Valid Range" descriptor is added, so that clients know the range.
+    pub fn valid_range<T: GattValue + PartialOrd + Send + Sync + 'static>(
+        &mut self,
+        minimum: T,
+        maximum: T,
+    ) -> &mut Self {
+        self.descriptor(&Descriptor::valid_range(&minimum, &maximum).build());
+
+        self.validate_writes(move |value| {
+            let value = T::decode(value).ok_or(esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN)?;
+
+            if minimum <= value && value <= maximum {
+                Ok(())
+            } else {
+                Err(esp_gatt_status_t_ESP_GATT_OUT_OF_RANGE)
+            }
+        })
+    }
+
+    /// Accepts only the written values of type `T` that are in the given list.
+    ///
+    /// Values that cannot be decoded are rejected with an "invalid attribute length" error,
+    /// the other ones with an "out of range" error.
+    pub fn allowed_values<T: GattValue + PartialEq + Send + Sync + 'static>(
+        &mut self,
+        values: impl IntoIterator<Item = T>,
+    ) -> &mut Self {
+        let values: Vec<T> = values.into_iter().collect();
+
+        self.validate_writes(move |value| {
+            let value = T::decode(value).ok_or(esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN)?;
+
+            if values.contains(&value) {
+                Ok(())
+            } else {
+                Err(esp_gatt_status_t_ESP_GATT_OUT_OF_RANGE)
+            }
+        })
+    }
+
+    /// Accepts only the written values that are valid UTF-8 strings.
+    ///
+    /// Other values are rejected with an "illegal parameter" error.
+    pub fn utf8(&mut self) -> &mut Self {
+        self.validate_writes(|value| {
+            std::str::from_utf8(value)
+                .map(|_| ())
+                .map_err(|_| esp_gatt_status_t_ESP_GATT_ILLEGAL_PARAMETER)
+        })
+    }
+}
@@ -1,26 +1,15 @@
 use crate::{
-    gatt_server::Descriptor,
-    utilities::{AttributePermissions, BleUuid},
+    gatt_server::{
+        broadcast::{read_sccd, write_sccd},
+        cccd::{read_cccd, write_cccd},
+        Characteristic, Descriptor,
+    },
+    utilities::{
+        formats::{Format, Uint24, Unit},
+        AttributePermissions, BleUuid, CharacteristicProperties, GattValue,
+    },
 };
 
-use std::sync::{Arc, Mutex};
-
-use esp_idf_svc::nvs::{EspDefaultNvs, EspDefaultNvsPartition};
-use lazy_static::lazy_static;
-use log::debug;
-
-lazy_static! {
-    static ref STORAGE: Arc<Mutex<EspDefaultNvs>> = Arc::new(Mutex::new(
-        EspDefaultNvs::new(
-            EspDefaultNvsPartition::take()
-                .expect("Cannot initialise the default NVS. Did you declare an NVS partition?"),
-            "ble",
-            true
-        )
-        .expect("Cannot create a new NVS storage. Did you declare an NVS partition?")
-    ));
-}
-
 impl Descriptor {
     /// Creates a new descriptor with the `0x2901` UUID, and the description string as its value.
     ///
@@ -37,13 +26,181 @@ impl Descriptor {
             .clone()
     }
 
+    /// Creates a new descriptor with the `0x2900` UUID, listing the extended properties of a characteristic.
+    ///
+    /// This descriptor is added automatically to the characteristics with the "extended properties" property.
+    #[must_use]
+    pub fn extended_properties(reliable_write: bool, writable_auxiliaries: bool) -> Self {
+        let flags = u16::from(reliable_write) | u16::from(writable_auxiliaries) << 1;
+
+        Self::new(BleUuid::from_uuid16(0x2900))
+            .name("Characteristic Extended Properties")
+            .permissions(AttributePermissions::new().read())
+            .set_value(flags.to_le_bytes().to_vec())
+            .clone()
+    }
+
+    /// Creates an SCCD, with the `0x2903` UUID.
+    ///
+    /// When a client enables broadcasting, the value of the characteristic is included
+    /// in the advertisement, as service data of its service, and updated when it changes.
+    /// The configuration is shared by all clients, and is lost on reboot.
+    ///
+    /// This descriptor is added automatically to the characteristics with the "broadcast" property.
+    #[must_use]
+    pub fn sccd() -> Self {
+        Self::new(BleUuid::from_uuid16(0x2903))
+            .name("Server Characteristic Configuration")
+            .permissions(AttributePermissions::new().read().write())
+            .on_read(
+                |param: esp_idf_sys::esp_ble_gatts_cb_param_t_gatts_read_evt_param| {
+                    read_sccd(param.handle).to_vec()
+                },
+            )
+            .on_write(|value, param| write_sccd(param.handle, &value))
+            .clone()
+    }
+
+    /// Creates a new descriptor with the `0x2906` UUID, with the range of values accepted by a characteristic.
+    ///
+    /// The bounds are inclusive, and must have the same format as the characteristic value.
+    #[must_use]
+    pub fn valid_range<T: GattValue>(minimum: &T, maximum: &T) -> Self {
+        let mut value = minimum.encode();
+        value.extend(maximum.encode());
+
+        Self::new(BleUuid::from_uuid16(0x2906))
+            .name("Valid Range")
+            .permissions(AttributePermissions::new().read())
+            .set_value(value)
+            .clone()
+    }
+
+    /// Creates an Environmental Sensing Configuration descriptor, with the `0x290B` UUID.
+    ///
+    /// The descriptor tells whether notifications are sent when all the trigger settings are met (`false`),
+    /// or when any of them is met (`true`). Clients can change it.
+    #[must_use]
+    pub fn es_configuration(any_trigger: bool) -> Self {
+        Self::new(BleUuid::from_uuid16(0x290B))
+            .name("Environmental Sensing Configuration")
+            .permissions(AttributePermissions::new().read().write())
+            .set_value(vec![u8::from(any_trigger)])
+            .clone()
+    }
+
+    /// Creates an Environmental Sensing Measurement descriptor, with the `0x290C` UUID.
+    ///
+    /// - `sampling_function`: `0x01` instantaneous, `0x02` arithmetic mean, `0x03` RMS, `0x04` maximum,
+    ///   `0x05` minimum, `0x06` accumulated, `0x07` count, or `0x00` if unspecified.
+    /// - `measurement_period` and `update_interval`: in seconds, or zero if not in use.
+    /// - `application`: the Environmental Sensing application, such as `0x01` for air or `0x02` for water.
+    /// - `uncertainty`: in units of 0.5%, or `0xFF` if not available.
+    #[must_use]
+    pub fn es_measurement(
+        sampling_function: u8,
+        measurement_period: Uint24,
+        update_interval: Uint24,
+        application: u8,
+        uncertainty: u8,
+    ) -> Self {
+        let value = (
+            0u16,
+            sampling_function,
+            measurement_period,
+            update_interval,
+            application,
+            uncertainty,
+        )
+            .encode();
+
+        Self::new(BleUuid::from_uuid16(0x290C))
+            .name("Environmental Sensing Measurement")
+            .permissions(AttributePermissions::new().read())
+            .set_value(value)
+            .clone()
+    }
+
+    /// Creates an Environmental Sensing Trigger Setting descriptor, with the `0x290D` UUID.
+    ///
+    /// The `condition` tells when notifications are sent, and the `operand` is its parameter,
+    /// encoded like the characteristic value or as a [`Uint24`] time interval, depending on the condition.
+    /// For example, `0x01` with a time interval notifies periodically, and `0x03` notifies when
+    /// the value differs from the operand. Clients can change it.
+    #[must_use]
+    pub fn es_trigger_setting<T: Into<Vec<u8>>>(condition: u8, operand: T) -> Self {
+        let mut value = vec![condition];
+        value.extend(operand.into());
+
+        Self::new(BleUuid::from_uuid16(0x290D))
+            .name("Environmental Sensing Trigger Setting")
+            .permissions(AttributePermissions::new().read().write())
+            .set_value(value)
+            .clone()
+    }
+
+    /// Creates a new descriptor with the `0x2904` UUID, describing how the characteristic value is displayed.
+    ///
+    /// The value is `format` × 10^`exponent`, in the given `unit`. The `description` is an identifier
+    /// defined by the organisation in `namespace`: use `0x01` and the Bluetooth SIG description
+    /// identifiers (for example, `0x0000` for "unknown"), or `0x00` if there is none.
+    ///
+    /// See [`Characteristic::presentation`] for an easier way to assign this kind of descriptor to a [`Characteristic`].
+    ///
+    /// [`Characteristic::presentation`]: crate::gatt_server::Characteristic::presentation
+    /// [`Characteristic`]: crate::gatt_server::Characteristic
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn presentation_format(
+        format: Format,
+        exponent: i8,
+        unit: Unit,
+        namespace: u8,
+        description: u16,
+    ) -> Self {
+        let [unit_low, unit_high] = unit.uuid16().to_le_bytes();
+        let [description_low, description_high] = description.to_le_bytes();
+
+        Self::new(BleUuid::from_uuid16(0x2904))
+            .name("Characteristic Presentation Format")
+            .permissions(AttributePermissions::new().read())
+            .set_value(vec![
+                format.into(),
+                exponent as u8,
+                unit_low,
+                unit_high,
+                namespace,
+                description_low,
+                description_high,
+            ])
+            .clone()
+    }
+
+    /// Creates a new descriptor with the `0x2905` UUID, listing the presentation formats of a characteristic value
+    /// made of `count` fields.
+    ///
+    /// The handles of the presentation format descriptors are filled in when they are registered.
+    /// This descriptor is added automatically to the characteristics with more than one presentation format.
+    #[must_use]
+    pub fn aggregate_format(count: usize) -> Self {
+        Self::new(BleUuid::from_uuid16(0x2905))
+            .name("Characteristic Aggregate Format")
+            .permissions(AttributePermissions::new().read())
+            .set_value(vec![0; count * 2])
+            .clone()
+    }
+
     /// Creates a CCCD.
     ///
-    /// The contents of the CCCD are stored in NVS and persisted across reboots.
+    /// The subscriptions of bonded clients are stored in NVS and persisted across reboots.
+    /// They are keyed by the identity address of the client and by the path of the characteristic,
+    /// so they survive changes of the attribute table. The subscriptions of other clients are lost
+    /// when they disconnect.
     ///
-    /// # Panics
+    /// The subscriptions are stored with the [`Storage`] set with [`GattServer::storage`].
     ///
-    /// Panics if the NVS is not configured.
+    /// [`Storage`]: crate::utilities::Storage
+    /// [`GattServer::storage`]: crate::gatt_server::GattServer::storage
     #[must_use]
     pub fn cccd() -> Self {
         Self::new(BleUuid::from_uuid16(0x2902))
@@ -51,53 +208,15 @@ impl Descriptor {
             .permissions(AttributePermissions::new().read().write())
             .on_read(
                 |param: esp_idf_sys::esp_ble_gatts_cb_param_t_gatts_read_evt_param| {
-                    let storage = STORAGE.lock().unwrap();
-
-                    // Get the descriptor handle.
-
-                    // TODO: Find the characteristic that contains the handle.
-                    // WARNING: Using the handle is incredibly stupid as the NVS is not erased across flashes.
-
-                    // Create a key from the connection address.
-                    let key = format!(
-                        "{:02X}{:02X}{:02X}{:02X}-{:04X}",
-                        /* param.bda[1], */ param.bda[2],
-                        param.bda[3],
-                        param.bda[4],
-                        param.bda[5],
-                        param.handle
-                    );
-
-                    // Prepare buffer and read correct CCCD value from non-volatile storage.
-                    let mut buf: [u8; 2] = [0; 2];
-                    if let Some(value) = storage.get_raw(&key, &mut buf).unwrap() {
-                        debug!("Read CCCD value: {:?} for key {}.", value, key);
-                        value.to_vec()
-                    } else {
-                        debug!("No CCCD value found for key {}.", key);
-                        vec![0, 0]
-                    }
+                    read_cccd(param.bda, param.handle).to_vec()
                 },
             )
             .on_write(|value, param| {
-                let mut storage = STORAGE.lock().unwrap();
-
-                // Create a key from the connection address.
-                let key = format!(
-                    "{:02X}{:02X}{:02X}{:02X}-{:04X}",
-                    /* param.bda[1], */ param.bda[2],
-                    param.bda[3],
-                    param.bda[4],
-                    param.bda[5],
-                    param.handle
-                );
-
-                debug!("Write CCCD value: {:?} at key {}", value, key);
-
-                // Write CCCD value to non-volatile storage.
-                storage
-                    .set_raw(&key, &value)
-                    .expect("Cannot put raw value to the NVS. Did you declare an NVS partition?");
+                let mut buf = [0u8; 2];
+                let length = value.len().min(buf.len());
+                buf[..length].copy_from_slice(&value[..length]);
+
+                write_cccd(param.bda, param.handle, buf);
             })
             .clone()
     }
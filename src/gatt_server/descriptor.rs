@@ -1,6 +1,7 @@
 use std::sync::{Arc, RwLock};
 
 use crate::{
+    gatt_server::authorization::Authorizer,
     leaky_box_raw,
     utilities::{AttributeControl, AttributePermissions, BleUuid},
 };
@@ -8,7 +9,7 @@ use crate::{
 use esp_idf_sys::{
     esp_attr_control_t, esp_attr_value_t, esp_ble_gatts_add_char_descr,
     esp_ble_gatts_cb_param_t_gatts_read_evt_param, esp_ble_gatts_cb_param_t_gatts_write_evt_param,
-    esp_ble_gatts_set_attr_value, esp_nofail,
+    esp_ble_gatts_set_attr_value, esp_nofail, ESP_GATT_RSP_BY_APP,
 };
 use log::{debug, info, warn};
 
@@ -17,12 +18,13 @@ use log::{debug, info, warn};
 pub struct Descriptor {
     name: Option<String>,
     pub(crate) uuid: BleUuid,
-    value: Vec<u8>,
+    pub(crate) value: Vec<u8>,
     pub(crate) attribute_handle: Option<u16>,
-    permissions: AttributePermissions,
+    pub(crate) permissions: AttributePermissions,
     pub(crate) control: AttributeControl,
     internal_control: esp_attr_control_t,
     pub(crate) write_callback: Option<fn(Vec<u8>, esp_ble_gatts_cb_param_t_gatts_write_evt_param)>,
+    pub(crate) authorizer: Option<Authorizer>,
 }
 
 impl Descriptor {
@@ -38,6 +40,7 @@ impl Descriptor {
             control: AttributeControl::AutomaticResponse(vec![0]),
             internal_control: AttributeControl::AutomaticResponse(vec![0]).into(),
             write_callback: None,
+            authorizer: None,
         }
     }
 
@@ -82,7 +85,7 @@ impl Descriptor {
         &mut self,
         callback: fn(Vec<u8>, esp_ble_gatts_cb_param_t_gatts_write_evt_param),
     ) -> &mut Self {
-        if !self.permissions.write_access {
+        if !self.permissions.writable() {
             warn!(
                 "Descriptor {} does not have write permissions. Ignoring write callback.",
                 self
@@ -128,12 +131,42 @@ impl Descriptor {
     pub fn build(&self) -> Arc<RwLock<Self>> {
         Arc::new(RwLock::new(self.clone()))
     }
+
+    /// Returns `true` if the requests to this [`Descriptor`] are answered by the library.
+    pub(crate) fn responds_by_app(&self) -> bool {
+        matches!(self.control, AttributeControl::ResponseByApp(_))
+            || self.permissions.requires_runtime_checks()
+            || self.authorizer.is_some()
+    }
+
+    /// Returns the current value of this [`Descriptor`], calling the read callback if there is one.
+    pub(crate) fn read_value(
+        &self,
+        param: esp_ble_gatts_cb_param_t_gatts_read_evt_param,
+    ) -> Vec<u8> {
+        match &self.control {
+            AttributeControl::ResponseByApp(callback) => callback(param),
+            AttributeControl::AutomaticResponse(_) => self.value.clone(),
+        }
+    }
+
+    /// Returns the maximum length of the value of this [`Descriptor`].
+    pub(crate) fn value_capacity(&self) -> usize {
+        self.value.len()
+    }
+
     pub(crate) fn register_self(&mut self, service_handle: u16) {
         debug!(
             "Registering {} into service at handle 0x{:04x}.",
             self, service_handle
         );
 
+        // The library must see every request that it has to check.
+        #[allow(clippy::cast_possible_truncation)]
+        if self.responds_by_app() {
+            self.internal_control.auto_rsp = ESP_GATT_RSP_BY_APP as u8;
+        }
+
         #[allow(clippy::cast_possible_truncation)]
         unsafe {
             esp_nofail!(esp_ble_gatts_add_char_descr(
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use esp_idf_sys::*;
+use log::{debug, info, warn};
+
+use crate::{
+    gatt_server::{GattServer, GLOBAL_GATT_SERVER},
+    utilities::{Connection, LinkSecurity},
+};
+
+/// The encryption that every client must set up right after connecting.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EncryptionRequirement {
+    mitm: bool,
+    timeout: Duration,
+}
+
+impl EncryptionRequirement {
+    /// Returns the minimum security level accepted for a link.
+    const fn minimum_security(&self) -> LinkSecurity {
+        if self.mitm {
+            LinkSecurity::Authenticated
+        } else {
+            LinkSecurity::Encrypted
+        }
+    }
+}
+
+impl GattServer {
+    /// Requests encryption as soon as a client connects, and disconnects the clients that do not set it up.
+    ///
+    /// Bonded clients encrypt the link with the stored keys, the others must pair.
+    /// With `mitm`, the keys must have been obtained with MITM protection.
+    /// Clients that fail or refuse pairing, or do not complete it within `timeout`, are disconnected.
+    ///
+    /// The mode must be set before starting the server.
+    pub fn require_pairing(&mut self, mitm: bool, timeout: Duration) -> &mut Self {
+        if self.started {
+            warn!("Cannot require pairing after the server has started.");
+            return self;
+        }
+
+        self.encryption_requirement = Some(EncryptionRequirement { mitm, timeout });
+        self
+    }
+
+    /// Asks a new client to encrypt the link, if required.
+    pub(crate) fn request_encryption(&self, connection: Connection) {
+        let Some(requirement) = self.encryption_requirement else {
+            return;
+        };
+
+        let action = if requirement.mitm {
+            esp_ble_sec_act_t_ESP_BLE_SEC_ENCRYPT_MITM
+        } else {
+            esp_ble_sec_act_t_ESP_BLE_SEC_ENCRYPT
+        };
+
+        debug!("Requesting encryption from {}.", connection);
+        let mut address = connection.remote_bda;
+        if let Err(error) = unsafe { esp!(esp_ble_set_encryption(address.as_mut_ptr(), action)) } {
+            warn!("Cannot request encryption from {}: {}.", connection, error);
+            Self::disconnect(address);
+            return;
+        }
+
+        // The lock is held while handling events, so the check must happen in another thread.
+        // The stack reuses connection identifiers, so the address must match as well.
+        let (conn_id, remote_bda) = (connection.id, connection.remote_bda);
+        std::thread::spawn(move || {
+            std::thread::sleep(requirement.timeout);
+
+            let server = GLOBAL_GATT_SERVER
+                .lock()
+                .expect("Cannot lock global GATT server.");
+
+            if let Some(current) = server
+                .active_connections
+                .iter()
+                .find(|current| current.id == conn_id && current.remote_bda == remote_bda)
+            {
+                if current.security() < requirement.minimum_security() {
+                    warn!("{} did not set up encryption in time.", current);
+                    Self::disconnect(current.remote_bda);
+                }
+            }
+        });
+    }
+
+    /// Disconnects a client whose link does not meet the required security level.
+    ///
+    /// The connection is identified by its identifier, because security events report
+    /// the identity address of the clients, which may differ from their connection address.
+    pub(crate) fn enforce_encryption(&self, conn_id: u16) {
+        let Some(requirement) = self.encryption_requirement else {
+            return;
+        };
+
+        if let Some(connection) = self
+            .active_connections
+            .iter()
+            .find(|connection| connection.id == conn_id)
+        {
+            if connection.security() < requirement.minimum_security() {
+                warn!(
+                    "{} does not meet the required security level ({:?}).",
+                    connection,
+                    requirement.minimum_security()
+                );
+                Self::disconnect(connection.remote_bda);
+            }
+        }
+    }
+
+    fn disconnect(mut address: [u8; 6]) {
+        info!("Disconnecting {:02X?}.", address);
+
+        if let Err(error) = unsafe { esp!(esp_ble_gap_disconnect(address.as_mut_ptr())) } {
+            warn!("Cannot disconnect {:02X?}: {}.", address, error);
+        }
+    }
+}
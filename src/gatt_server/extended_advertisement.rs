@@ -0,0 +1,649 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use esp_idf_sys::*;
+use log::{debug, info, warn};
+
+use crate::{gatt_server::GattServer, utilities::Phy};
+
+/// The maximum length of an extended advertisement payload.
+const MAX_EXTENDED_DATA_LENGTH: usize = 1650;
+
+/// The maximum length of a legacy advertisement payload.
+const MAX_LEGACY_DATA_LENGTH: usize = 31;
+
+/// The minimum advertising interval, 0x20 units of 0.625 ms.
+const MIN_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The maximum advertising interval, 0xFFFFFF units of 0.625 ms.
+const MAX_INTERVAL: Duration = Duration::from_micros(0xFF_FFFF * 625);
+
+/// The maximum advertising duration, 0xFFFF units of 10 ms.
+const MAX_DURATION: Duration = Duration::from_millis(0xFFFF * 10);
+
+/// Represents a BLE 5 extended advertising set.
+///
+/// Multiple sets can be advertised concurrently, each with its own parameters, payload and PHYs.
+///
+/// # Notes
+///
+/// Extended advertising is only available on chips that support BLE 5 (ESP32-C3, ESP32-S3),
+/// with `CONFIG_BT_BLE_50_FEATURES_SUPPORTED` enabled.
+/// On other chips, [`ExtendedAdvertisement::start`] and [`ExtendedAdvertisement::stop`] return
+/// an `ESP_ERR_NOT_SUPPORTED` error.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(esp_idf_bt_ble_50_features_supported), allow(dead_code))]
+pub struct ExtendedAdvertisement {
+    name: Option<String>,
+    pub(crate) instance: u8,
+    connectable: bool,
+    scannable: bool,
+    legacy: bool,
+    anonymous: bool,
+    include_tx_power: bool,
+    interval: (Duration, Duration),
+    primary_phy: Phy,
+    secondary_phy: Phy,
+    tx_power: i8,
+    sid: u8,
+    pub(crate) data: Vec<u8>,
+    pub(crate) scan_response_data: Option<Vec<u8>>,
+    pub(crate) own_address_type: esp_ble_addr_type_t,
+    /// The start parameters, waiting for the set to be configured: duration in 10 ms units and maximum events.
+    pub(crate) pending_start: Option<(i32, i32)>,
+    /// Whether the stack has acknowledged the parameters of the set.
+    pub(crate) configured: bool,
+    pub(crate) active: bool,
+}
+
+impl ExtendedAdvertisement {
+    /// Creates a new [`ExtendedAdvertisement`] on the given advertising set instance.
+    ///
+    /// Each set must have a different instance.
+    #[must_use]
+    pub fn new(instance: u8) -> Self {
+        Self {
+            name: None,
+            instance,
+            connectable: false,
+            scannable: false,
+            legacy: false,
+            anonymous: false,
+            include_tx_power: false,
+            interval: (Duration::from_millis(100), Duration::from_millis(150)),
+            primary_phy: Phy::Le1M,
+            secondary_phy: Phy::Le1M,
+            tx_power: 0x7F,
+            sid: instance,
+            data: Vec::new(),
+            scan_response_data: None,
+            own_address_type: esp_ble_addr_type_t_BLE_ADDR_TYPE_PUBLIC,
+            pending_start: None,
+            configured: false,
+            active: false,
+        }
+    }
+
+    /// Sets the name of the [`ExtendedAdvertisement`].
+    ///
+    /// This name is only used for debugging purposes.
+    pub fn name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Makes the advertising set connectable.
+    ///
+    /// Extended advertisements cannot be both connectable and scannable.
+    pub fn connectable(&mut self) -> &mut Self {
+        if self.scannable && !self.legacy {
+            warn!("Extended advertisements cannot be both connectable and scannable. Ignoring connectable.");
+            return self;
+        }
+
+        self.connectable = true;
+        self
+    }
+
+    /// Makes the advertising set scannable, so that a scan response can be sent.
+    ///
+    /// Extended advertisements cannot be both connectable and scannable.
+    pub fn scannable(&mut self) -> &mut Self {
+        if self.connectable && !self.legacy {
+            warn!("Extended advertisements cannot be both connectable and scannable. Ignoring scannable.");
+            return self;
+        }
+
+        self.scannable = true;
+        self
+    }
+
+    /// Uses legacy advertising PDUs for this set, so that it can be seen by BLE 4 scanners.
+    ///
+    /// Legacy PDUs are limited to 31 bytes of payload and to the LE 1M PHY.
+    pub fn legacy(&mut self) -> &mut Self {
+        self.legacy = true;
+        self.primary_phy = Phy::Le1M;
+        self.secondary_phy = Phy::Le1M;
+        self
+    }
+
+    /// Omits the advertiser's address from the advertising PDUs.
+    pub fn anonymous(&mut self) -> &mut Self {
+        self.anonymous = true;
+        self
+    }
+
+    /// Includes the transmit power in the extended header of the advertising PDUs.
+    pub fn include_tx_power(&mut self) -> &mut Self {
+        self.include_tx_power = true;
+        self
+    }
+
+    /// Sets the minimum and maximum advertising interval.
+    ///
+    /// The interval is rounded to the nearest multiple of 0.625 ms,
+    /// and must be between 20 ms and 10485.759375 s.
+    pub fn interval(&mut self, min: Duration, max: Duration) -> &mut Self {
+        if min > max {
+            warn!(
+                "The minimum advertising interval is greater than the maximum. Ignoring interval."
+            );
+            return self;
+        }
+
+        if min < MIN_INTERVAL || max > MAX_INTERVAL {
+            warn!(
+                "The advertising interval must be between {:?} and {:?}. Ignoring interval.",
+                MIN_INTERVAL, MAX_INTERVAL
+            );
+            return self;
+        }
+
+        self.interval = (min, max);
+        self
+    }
+
+    /// Sets the PHY used on the primary advertising channels.
+    ///
+    /// Only the LE 1M and the LE Coded PHYs are allowed. Use the LE Coded PHY for long range.
+    pub fn primary_phy(&mut self, phy: Phy) -> &mut Self {
+        if phy == Phy::Le2M {
+            warn!("The LE 2M PHY cannot be used on the primary advertising channels. Ignoring primary PHY.");
+            return self;
+        }
+
+        if self.legacy && phy != Phy::Le1M {
+            warn!("Legacy advertisements can only use the LE 1M PHY. Ignoring primary PHY.");
+            return self;
+        }
+
+        self.primary_phy = phy;
+        self
+    }
+
+    /// Sets the PHY used on the secondary advertising channels, where the payload is sent.
+    pub fn secondary_phy(&mut self, phy: Phy) -> &mut Self {
+        if self.legacy && phy != Phy::Le1M {
+            warn!("Legacy advertisements can only use the LE 1M PHY. Ignoring secondary PHY.");
+            return self;
+        }
+
+        self.secondary_phy = phy;
+        self
+    }
+
+    /// Sets the requested transmit power, in dBm.
+    ///
+    /// The controller picks the closest supported value.
+    pub fn tx_power(&mut self, power: i8) -> &mut Self {
+        self.tx_power = power;
+        self
+    }
+
+    /// Sets the advertising set identifier, which defaults to the instance.
+    pub fn sid(&mut self, sid: u8) -> &mut Self {
+        if sid > 0x0F {
+            warn!("The advertising SID must be between 0x00 and 0x0F. Ignoring SID.");
+            return self;
+        }
+
+        self.sid = sid;
+        self
+    }
+
+    /// Sets the advertisement payload.
+    ///
+    /// The payload can be up to 1650 bytes long, or 31 bytes for legacy advertisements.
+    /// See [`AdvertisementData`] for an easy way of building a payload.
+    ///
+    /// If the set is already advertising, the payload is updated on the fly.
+    ///
+    /// [`AdvertisementData`]: crate::utilities::AdvertisementData
+    pub fn data<T: Into<Vec<u8>>>(&mut self, data: T) -> &mut Self {
+        let data = data.into();
+
+        if !self.check_length(&data) {
+            return self;
+        }
+
+        self.data = data;
+
+        if self.active {
+            self.configure_data();
+        }
+
+        self
+    }
+
+    /// Sets the scan response payload.
+    ///
+    /// The set must be scannable. The same length limits as [`ExtendedAdvertisement::data`] apply.
+    pub fn scan_response_data<T: Into<Vec<u8>>>(&mut self, data: T) -> &mut Self {
+        let data = data.into();
+
+        if !self.scannable {
+            warn!("{} is not scannable. Ignoring scan response data.", self);
+            return self;
+        }
+
+        if !self.check_length(&data) {
+            return self;
+        }
+
+        self.scan_response_data = Some(data);
+
+        if self.active {
+            self.configure_scan_response_data();
+        }
+
+        self
+    }
+
+    /// Returns a reference to the built [`ExtendedAdvertisement`] behind an `Arc` and an `RwLock`.
+    ///
+    /// The returned value can be passed to any function of this crate that expects an [`ExtendedAdvertisement`].
+    /// It can be used in different threads, because it is protected by an `RwLock`.
+    #[must_use]
+    pub fn build(&self) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(self.clone()))
+    }
+
+    /// Returns `true` if the set is currently advertising.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Starts advertising this set.
+    ///
+    /// The set stops after the given duration or after the given number of advertising events,
+    /// whichever comes first. Pass `None` for no limit.
+    /// The duration is rounded down to a multiple of 10 ms, and cannot exceed 655.35 s.
+    ///
+    /// The set must have been added to the server with [`GattServer::extended_advertisement`],
+    /// and the server must be started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ESP_ERR_INVALID_ARG` error if the duration is too long.
+    /// Returns an error if the Bluetooth stack rejects the parameters,
+    /// or if extended advertising is not supported on this chip.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn start(
+        &mut self,
+        duration: Option<Duration>,
+        max_events: Option<u8>,
+    ) -> Result<(), EspError> {
+        let duration = match duration {
+            None => 0,
+            Some(duration) if duration > MAX_DURATION => {
+                warn!(
+                    "Cannot start {} for {:?}. The maximum duration is {:?}.",
+                    self, duration, MAX_DURATION
+                );
+                return Err(EspError::from(ESP_ERR_INVALID_ARG as esp_err_t).unwrap());
+            }
+            // A duration of 0 means no limit, so shorter durations are rounded up to 10 ms.
+            Some(duration) => (duration.as_millis() / 10).max(1) as i32,
+        };
+        let max_events = max_events.map_or(0, i32::from);
+
+        info!("Starting {}.", self);
+        self.pending_start = Some((duration, max_events));
+
+        // The payload is configured and advertising is started as soon as the stack acknowledges the parameters.
+        self.configure_parameters()
+    }
+
+    /// Stops advertising this set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot stop the set,
+    /// or if extended advertising is not supported on this chip.
+    pub fn stop(&mut self) -> Result<(), EspError> {
+        self.pending_start = None;
+        self.stop_set()
+    }
+
+    /// Returns `true` if the set can carry a periodic advertisement.
+    pub(crate) const fn supports_periodic_advertising(&self) -> bool {
+        !(self.connectable || self.scannable || self.legacy || self.anonymous)
+    }
+
+    fn check_length(&self, data: &[u8]) -> bool {
+        let max_length = if self.legacy {
+            MAX_LEGACY_DATA_LENGTH
+        } else {
+            MAX_EXTENDED_DATA_LENGTH
+        };
+
+        if data.len() > max_length {
+            warn!(
+                "Payload is too long for {}. The maximum length is {} bytes. Ignoring payload.",
+                self, max_length
+            );
+            return false;
+        }
+
+        true
+    }
+
+    #[cfg(esp_idf_bt_ble_50_features_supported)]
+    #[allow(clippy::cast_possible_truncation)]
+    fn configure_parameters(&self) -> Result<(), EspError> {
+        let mut properties = 0;
+        if self.connectable {
+            properties |= ESP_BLE_GAP_SET_EXT_ADV_PROP_CONNECTABLE;
+        }
+        if self.scannable {
+            properties |= ESP_BLE_GAP_SET_EXT_ADV_PROP_SCANNABLE;
+        }
+        if self.legacy {
+            properties |= ESP_BLE_GAP_SET_EXT_ADV_PROP_LEGACY;
+        }
+        if self.anonymous {
+            properties |= ESP_BLE_GAP_SET_EXT_ADV_PROP_ANON;
+        }
+        if self.include_tx_power {
+            properties |= ESP_BLE_GAP_SET_EXT_ADV_PROP_INCLUDE_TX_PWR;
+        }
+
+        // The interval is expressed in units of 0.625 ms.
+        let to_units = |interval: Duration| (interval.as_micros() / 625) as u32;
+
+        let parameters = esp_ble_gap_ext_adv_params_t {
+            type_: properties as _,
+            interval_min: to_units(self.interval.0),
+            interval_max: to_units(self.interval.1),
+            channel_map: esp_ble_adv_channel_t_ADV_CHNL_ALL,
+            own_addr_type: self.own_address_type,
+            filter_policy: esp_ble_adv_filter_t_ADV_FILTER_ALLOW_SCAN_ANY_CON_ANY,
+            tx_power: self.tx_power,
+            primary_phy: u8::from(self.primary_phy) as _,
+            max_skip: 0,
+            secondary_phy: u8::from(self.secondary_phy) as _,
+            sid: self.sid,
+            scan_req_notif: false,
+            ..Default::default()
+        };
+
+        unsafe { esp!(esp_ble_gap_ext_adv_set_params(self.instance, &parameters)) }
+    }
+
+    #[cfg(not(esp_idf_bt_ble_50_features_supported))]
+    #[allow(clippy::unused_self)]
+    fn configure_parameters(&self) -> Result<(), EspError> {
+        Err(not_supported())
+    }
+
+    #[cfg(esp_idf_bt_ble_50_features_supported)]
+    fn stop_set(&self) -> Result<(), EspError> {
+        unsafe { esp!(esp_ble_gap_ext_adv_stop(1, &self.instance)) }
+    }
+
+    #[cfg(not(esp_idf_bt_ble_50_features_supported))]
+    #[allow(clippy::unused_self)]
+    fn stop_set(&self) -> Result<(), EspError> {
+        Err(not_supported())
+    }
+
+    /// Sends the advertisement payload to the stack.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn configure_data(&self) {
+        debug!("Configuring payload of {}.", self);
+
+        #[cfg(esp_idf_bt_ble_50_features_supported)]
+        unsafe {
+            esp_nofail!(esp_ble_gap_config_ext_adv_data_raw(
+                self.instance,
+                self.data.len() as u16,
+                self.data.as_ptr()
+            ));
+        }
+    }
+
+    /// Sends the scan response payload to the stack.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn configure_scan_response_data(&self) {
+        debug!("Configuring scan response payload of {}.", self);
+
+        #[cfg(esp_idf_bt_ble_50_features_supported)]
+        if let Some(scan_response_data) = &self.scan_response_data {
+            unsafe {
+                esp_nofail!(esp_ble_gap_config_ext_scan_rsp_data_raw(
+                    self.instance,
+                    scan_response_data.len() as u16,
+                    scan_response_data.as_ptr()
+                ));
+            }
+        }
+    }
+
+    /// Starts advertising, if a start has been requested.
+    #[cfg(esp_idf_bt_ble_50_features_supported)]
+    pub(crate) fn start_pending(&mut self) {
+        let Some((duration, max_events)) = self.pending_start.take() else {
+            return;
+        };
+
+        let parameters = esp_ble_gap_ext_adv_t {
+            instance: self.instance,
+            duration,
+            max_events,
+        };
+
+        unsafe {
+            esp_nofail!(esp_ble_gap_ext_adv_start(1, &parameters));
+        }
+    }
+}
+
+impl std::fmt::Display for ExtendedAdvertisement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (instance {})",
+            self.name
+                .clone()
+                .unwrap_or_else(|| "Unnamed advertising set".to_string()),
+            self.instance,
+        )
+    }
+}
+
+/// Returns the error used when BLE 5 features are not available.
+#[cfg(not(esp_idf_bt_ble_50_features_supported))]
+pub(crate) fn not_supported() -> EspError {
+    warn!("BLE 5 features are not supported on this chip, or CONFIG_BT_BLE_50_FEATURES_SUPPORTED is disabled.");
+    EspError::from(ESP_ERR_NOT_SUPPORTED as esp_err_t).unwrap()
+}
+
+impl GattServer {
+    /// Adds an [`ExtendedAdvertisement`] set to the server.
+    ///
+    /// The set does not start advertising until [`ExtendedAdvertisement::start`] is called.
+    pub fn extended_advertisement(
+        &mut self,
+        advertisement: &Arc<RwLock<ExtendedAdvertisement>>,
+    ) -> &mut Self {
+        let instance = advertisement.read().unwrap().instance;
+        if self.get_extended_advertisement(instance).is_some() {
+            warn!(
+                "An advertising set with instance {} already exists. Ignoring advertising set.",
+                instance
+            );
+            return self;
+        }
+
+        self.apply_own_address_type(&mut advertisement.write().unwrap());
+        self.extended_advertisements.push(advertisement.clone());
+        self
+    }
+
+    /// Makes an advertising set use the same address type as the legacy advertisement.
+    ///
+    /// Returns `true` if the address type changed.
+    pub(crate) fn apply_own_address_type(&self, advertisement: &mut ExtendedAdvertisement) -> bool {
+        let own_address_type = self.advertisement_parameters.own_addr_type;
+        if advertisement.own_address_type == own_address_type {
+            return false;
+        }
+
+        debug!(
+            "Using address type {} for {}.",
+            own_address_type, advertisement
+        );
+        advertisement.own_address_type = own_address_type;
+        true
+    }
+
+    pub(crate) fn get_extended_advertisement(
+        &self,
+        instance: u8,
+    ) -> Option<Arc<RwLock<ExtendedAdvertisement>>> {
+        self.extended_advertisements
+            .iter()
+            .find(|advertisement| advertisement.read().unwrap().instance == instance)
+            .cloned()
+    }
+}
+
+#[cfg(esp_idf_bt_ble_50_features_supported)]
+impl GattServer {
+    /// Handles the completion of the parameters configuration of an advertising set.
+    pub(crate) fn on_ext_adv_set_params(&mut self, status: esp_bt_status_t, instance: u8) {
+        let Some(advertisement) = self.get_extended_advertisement(instance) else {
+            warn!("Cannot find advertising set with instance {}.", instance);
+            return;
+        };
+
+        if status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "Cannot set parameters of {}.",
+                advertisement.read().unwrap()
+            );
+            let mut advertisement = advertisement.write().unwrap();
+            advertisement.pending_start = None;
+            advertisement.configured = false;
+            return;
+        }
+
+        // The local address may have changed since the parameters were sent.
+        {
+            let mut advertisement = advertisement.write().unwrap();
+            advertisement.configured = true;
+
+            if self.apply_own_address_type(&mut advertisement) {
+                if let Err(error) = advertisement.configure_parameters() {
+                    warn!("Cannot set parameters of {}: {}.", advertisement, error);
+                    advertisement.pending_start = None;
+                }
+                return;
+            }
+        }
+
+        // Random addresses must be configured per advertising set.
+        if let Some(mut address) = self.random_address() {
+            unsafe {
+                esp_nofail!(esp_ble_gap_ext_adv_set_rand_addr(
+                    instance,
+                    address.as_mut_ptr()
+                ));
+            }
+            return;
+        }
+
+        advertisement.read().unwrap().configure_data();
+    }
+
+    /// Handles the completion of the random address configuration of an advertising set.
+    pub(crate) fn on_ext_adv_set_rand_addr(&mut self, status: esp_bt_status_t, instance: u8) {
+        let Some(advertisement) = self.get_extended_advertisement(instance) else {
+            warn!("Cannot find advertising set with instance {}.", instance);
+            return;
+        };
+
+        if status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "Cannot set random address of {}.",
+                advertisement.read().unwrap()
+            );
+        }
+
+        advertisement.read().unwrap().configure_data();
+    }
+
+    /// Handles the completion of the payload configuration of an advertising set.
+    pub(crate) fn on_ext_adv_data_set(&mut self, status: esp_bt_status_t, instance: u8) {
+        let Some(advertisement) = self.get_extended_advertisement(instance) else {
+            warn!("Cannot find advertising set with instance {}.", instance);
+            return;
+        };
+
+        if status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!("Cannot set payload of {}.", advertisement.read().unwrap());
+        }
+
+        let mut advertisement = advertisement.write().unwrap();
+        if advertisement.scan_response_data.is_some() {
+            advertisement.configure_scan_response_data();
+        } else {
+            advertisement.start_pending();
+        }
+    }
+
+    /// Handles the completion of the scan response configuration of an advertising set.
+    pub(crate) fn on_ext_scan_rsp_data_set(&mut self, status: esp_bt_status_t, instance: u8) {
+        let Some(advertisement) = self.get_extended_advertisement(instance) else {
+            warn!("Cannot find advertising set with instance {}.", instance);
+            return;
+        };
+
+        if status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "Cannot set scan response payload of {}.",
+                advertisement.read().unwrap()
+            );
+        }
+
+        advertisement.write().unwrap().start_pending();
+    }
+
+    /// Marks the given advertising sets as active or inactive.
+    pub(crate) fn set_ext_adv_active(&mut self, instances: &[u8], active: bool) {
+        for instance in instances {
+            if let Some(advertisement) = self.get_extended_advertisement(*instance) {
+                debug!(
+                    "{} {}.",
+                    advertisement.read().unwrap(),
+                    if active { "started" } else { "stopped" }
+                );
+                advertisement.write().unwrap().active = active;
+            }
+        }
+    }
+}
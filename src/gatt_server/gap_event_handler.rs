@@ -1,16 +1,52 @@
 use esp_idf_sys::{
-    esp_ble_gap_cb_param_t, esp_ble_gap_start_advertising, esp_bt_status_t_ESP_BT_STATUS_SUCCESS,
-    esp_gap_ble_cb_event_t, esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_DATA_SET_COMPLETE_EVT,
+    esp_ble_gap_cb_param_t, esp_bt_status_t_ESP_BT_STATUS_SUCCESS, esp_gap_ble_cb_event_t,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_DATA_RAW_SET_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_DATA_SET_COMPLETE_EVT,
     esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_START_COMPLETE_EVT,
     esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_STOP_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_AUTH_CMPL_EVT, esp_gap_ble_cb_event_t_ESP_GAP_BLE_KEY_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_NC_REQ_EVT, esp_gap_ble_cb_event_t_ESP_GAP_BLE_OOB_REQ_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_PASSKEY_NOTIF_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_PASSKEY_REQ_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_READ_RSSI_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_REMOVE_BOND_DEV_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SCAN_RSP_DATA_RAW_SET_COMPLETE_EVT,
     esp_gap_ble_cb_event_t_ESP_GAP_BLE_SCAN_RSP_DATA_SET_COMPLETE_EVT,
-    esp_gap_ble_cb_event_t_ESP_GAP_BLE_UPDATE_CONN_PARAMS_EVT, esp_nofail,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SEC_REQ_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_LOCAL_PRIVACY_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_PKT_LENGTH_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_STATIC_RAND_ADDR_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_UPDATE_CONN_PARAMS_EVT,
 };
 
-use log::{debug, info, warn};
+#[cfg(esp_idf_bt_ble_50_features_supported)]
+use esp_idf_sys::{
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_TERMINATED_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_DATA_SET_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_SET_PARAMS_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_SET_RAND_ADDR_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_START_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_STOP_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_SCAN_RSP_DATA_SET_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_DATA_SET_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_SET_PARAMS_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_START_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_STOP_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_PHY_UPDATE_COMPLETE_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_PREFERRED_PHY_COMPLETE_EVT,
+};
+
+#[cfg(esp_idf_version_major = "5")]
+use esp_idf_sys::{
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SC_CR_LOC_OOB_EVT,
+    esp_gap_ble_cb_event_t_ESP_GAP_BLE_SC_OOB_REQ_EVT,
+};
+
+use log::{debug, warn};
 
 use super::GattServer;
-use crate::leaky_box_raw;
+#[cfg(esp_idf_version_major = "5")]
+use super::OobData;
 
 impl GattServer {
     pub(crate) extern "C" fn gap_event_handler(
@@ -22,28 +58,25 @@ impl GattServer {
         match event {
             esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_DATA_SET_COMPLETE_EVT => {
                 debug!("BLE GAP advertisement data set complete.");
-                info!("Starting BLE GAP advertisement.");
-
-                unsafe {
-                    esp_nofail!(esp_ble_gap_start_advertising(leaky_box_raw!(
-                        self.advertisement_parameters
-                    )));
-                }
+                self.restart_advertising();
             }
             esp_gap_ble_cb_event_t_ESP_GAP_BLE_SCAN_RSP_DATA_SET_COMPLETE_EVT => {
                 debug!("BLE GAP scan response data set complete.");
-                info!("Starting BLE GAP response advertisement.");
-
-                unsafe {
-                    esp_nofail!(esp_ble_gap_start_advertising(leaky_box_raw!(
-                        self.advertisement_parameters
-                    )));
-                }
+                self.restart_advertising();
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_DATA_RAW_SET_COMPLETE_EVT => {
+                debug!("BLE GAP raw advertisement data set complete.");
+                self.restart_advertising();
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SCAN_RSP_DATA_RAW_SET_COMPLETE_EVT => {
+                debug!("BLE GAP raw scan response data set complete.");
+                self.restart_advertising();
             }
             esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_START_COMPLETE_EVT => {
                 let param = unsafe { (*param).adv_data_cmpl };
                 if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
                     debug!("BLE GAP advertisement started.");
+                    self.check_local_address_change();
                 } else {
                     warn!("BLE GAP advertisement start failed.");
                 }
@@ -56,9 +89,161 @@ impl GattServer {
                     warn!("BLE GAP advertisement stop failed.");
                 }
             }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_STATIC_RAND_ADDR_EVT => {
+                let param = unsafe { (*param).set_rand_addr_cmpl };
+                if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+                    debug!("BLE GAP random static address set.");
+                } else {
+                    warn!("BLE GAP random static address configuration failed.");
+                }
+
+                self.configure_advertisement_data();
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_LOCAL_PRIVACY_COMPLETE_EVT => {
+                let param = unsafe { (*param).local_privacy_cmpl };
+                if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+                    debug!("BLE GAP local privacy enabled.");
+                    Self::spawn_local_address_monitor();
+                } else {
+                    warn!("BLE GAP local privacy configuration failed.");
+                }
+
+                self.configure_advertisement_data();
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_SET_PARAMS_COMPLETE_EVT => {
+                let param = unsafe { (*param).ext_adv_set_params };
+                self.on_ext_adv_set_params(param.status, param.instance);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_SET_RAND_ADDR_COMPLETE_EVT => {
+                let param = unsafe { (*param).ext_adv_set_rand_addr };
+                self.on_ext_adv_set_rand_addr(param.status, param.instance);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_DATA_SET_COMPLETE_EVT => {
+                let param = unsafe { (*param).ext_adv_data_set };
+                self.on_ext_adv_data_set(param.status, param.instance);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_SCAN_RSP_DATA_SET_COMPLETE_EVT => {
+                let param = unsafe { (*param).scan_rsp_set };
+                self.on_ext_scan_rsp_data_set(param.status, param.instance);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_START_COMPLETE_EVT => {
+                let param = unsafe { (*param).ext_adv_start };
+                if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+                    self.set_ext_adv_active(&param.instance[..param.instance_num as usize], true);
+                } else {
+                    warn!("BLE GAP extended advertisement start failed.");
+                }
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_EXT_ADV_STOP_COMPLETE_EVT => {
+                let param = unsafe { (*param).ext_adv_stop };
+                if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+                    self.set_ext_adv_active(&param.instance[..param.instance_num as usize], false);
+                } else {
+                    warn!("BLE GAP extended advertisement stop failed.");
+                }
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_ADV_TERMINATED_EVT => {
+                let param = unsafe { (*param).adv_terminate };
+                debug!(
+                    "BLE GAP extended advertisement terminated with status {}.",
+                    param.status
+                );
+                self.set_ext_adv_active(&[param.adv_instance], false);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_SET_PARAMS_COMPLETE_EVT => {
+                let param = unsafe { (*param).peroid_adv_set_params };
+                self.on_periodic_adv_set_params(param.status, param.instance);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_DATA_SET_COMPLETE_EVT => {
+                let param = unsafe { (*param).period_adv_data_set };
+                self.on_periodic_adv_data_set(param.status, param.instance);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_START_COMPLETE_EVT => {
+                let param = unsafe { (*param).period_adv_start };
+                self.on_periodic_adv_start_stop(param.status, param.instance, true);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_PERIODIC_ADV_STOP_COMPLETE_EVT => {
+                let param = unsafe { (*param).period_adv_stop };
+                self.on_periodic_adv_start_stop(param.status, param.instance, false);
+            }
             esp_gap_ble_cb_event_t_ESP_GAP_BLE_UPDATE_CONN_PARAMS_EVT => {
                 let param = unsafe { (*param).update_conn_params };
-                info!("Connection parameters updated: {:?}", param);
+                self.on_connection_parameters_update(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_PKT_LENGTH_COMPLETE_EVT => {
+                let param = unsafe { (*param).pkt_data_lenth_cmpl };
+                self.on_data_length_update(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_READ_RSSI_COMPLETE_EVT => {
+                let param = unsafe { (*param).read_rssi_cmpl };
+                self.on_rssi_read(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_PASSKEY_NOTIF_EVT => {
+                let param = unsafe { (*param).ble_security.key_notif };
+                self.on_passkey_notification(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_PASSKEY_REQ_EVT => {
+                let param = unsafe { (*param).ble_security.ble_req };
+                self.on_passkey_request_event(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_NC_REQ_EVT => {
+                let param = unsafe { (*param).ble_security.key_notif };
+                self.on_numeric_comparison_event(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SEC_REQ_EVT => {
+                let param = unsafe { (*param).ble_security.ble_req };
+                self.on_security_request_event(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_OOB_REQ_EVT => {
+                let param = unsafe { (*param).ble_security.ble_req };
+                self.on_oob_request_event(param);
+            }
+            #[cfg(esp_idf_version_major = "5")]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SC_OOB_REQ_EVT => {
+                let param = unsafe { (*param).ble_security.ble_req };
+                self.on_sc_oob_request_event(param);
+            }
+            #[cfg(esp_idf_version_major = "5")]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SC_CR_LOC_OOB_EVT => {
+                let param = unsafe { (*param).ble_security.oob_data };
+                self.on_local_oob_data_event(OobData::new(param.oob_c, param.oob_r));
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_KEY_EVT => {
+                let param = unsafe { (*param).ble_security.ble_key };
+                self.on_key_event(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_AUTH_CMPL_EVT => {
+                let param = unsafe { (*param).ble_security.auth_cmpl };
+                self.on_authentication_complete_event(param);
+            }
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_REMOVE_BOND_DEV_COMPLETE_EVT => {
+                let param = unsafe { (*param).remove_bond_dev_cmpl };
+                self.on_bond_removed(param);
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_SET_PREFERRED_PHY_COMPLETE_EVT => {
+                let param = unsafe { (*param).set_perf_phy };
+                if param.status == esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+                    debug!("BLE GAP preferred PHY set.");
+                } else {
+                    warn!("BLE GAP preferred PHY configuration failed.");
+                }
+            }
+            #[cfg(esp_idf_bt_ble_50_features_supported)]
+            esp_gap_ble_cb_event_t_ESP_GAP_BLE_PHY_UPDATE_COMPLETE_EVT => {
+                let param = unsafe { (*param).phy_update };
+                self.on_phy_update(param);
             }
             _ => {
                 warn!("Unhandled GAP event: {:?}", event);
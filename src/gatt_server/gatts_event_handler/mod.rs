@@ -1,4 +1,7 @@
-use crate::gatt_server::{GattServer, Profile};
+use crate::{
+    gatt_server::{GattServer, Profile},
+    utilities::Connection,
+};
 
 #[allow(clippy::wildcard_imports)]
 use esp_idf_sys::*;
@@ -63,6 +66,23 @@ impl GattServer {
             _ => {}
         }
 
+        // Find the peer of read and write requests, to check the attribute permissions.
+        #[allow(non_upper_case_globals)]
+        let address = match event {
+            esp_gatts_cb_event_t_ESP_GATTS_READ_EVT => Some(unsafe { (*param).read.bda }),
+            esp_gatts_cb_event_t_ESP_GATTS_WRITE_EVT => Some(unsafe { (*param).write.bda }),
+            esp_gatts_cb_event_t_ESP_GATTS_EXEC_WRITE_EVT => {
+                Some(unsafe { (*param).exec_write.bda })
+            }
+            _ => None,
+        };
+        let connection = address.and_then(|address| {
+            self.active_connections
+                .iter()
+                .find(|connection| connection.has_address(address))
+                .copied()
+        });
+
         self.profiles.iter().for_each(|profile| {
             if profile.read().unwrap().interface == Some(gatts_if) {
                 debug!(
@@ -73,7 +93,7 @@ impl GattServer {
                 profile
                     .write()
                     .unwrap()
-                    .gatts_event_handler(event, gatts_if, param);
+                    .gatts_event_handler(event, gatts_if, param, connection.as_ref());
             }
         });
     }
@@ -86,6 +106,7 @@ impl Profile {
         event: esp_gatts_cb_event_t,
         gatts_if: esp_gatt_if_t,
         param: *mut esp_ble_gatts_cb_param_t,
+        connection: Option<&Connection>,
     ) {
         #[allow(non_upper_case_globals)]
         match event {
@@ -117,12 +138,17 @@ impl Profile {
             esp_gatts_cb_event_t_ESP_GATTS_WRITE_EVT => {
                 let param = unsafe { (*param).write };
 
-                self.on_write(gatts_if, param);
+                self.on_write(gatts_if, param, connection);
+            }
+            esp_gatts_cb_event_t_ESP_GATTS_EXEC_WRITE_EVT => {
+                let param = unsafe { (*param).exec_write };
+
+                self.on_exec_write(gatts_if, param, connection);
             }
             esp_gatts_cb_event_t_ESP_GATTS_READ_EVT => {
                 let param = unsafe { (*param).read };
 
-                self.on_read(gatts_if, param);
+                self.on_read(gatts_if, param, connection);
             }
             esp_gatts_cb_event_t_ESP_GATTS_CONF_EVT => {
                 let _param = unsafe { (*param).conf };
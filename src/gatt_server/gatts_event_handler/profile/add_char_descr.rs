@@ -1,4 +1,4 @@
-use crate::gatt_server::Profile;
+use crate::gatt_server::{broadcast::register_sccd, cccd::register_cccd_path, Profile};
 use crate::utilities::BleUuid;
 use esp_idf_sys::*;
 use log::{info, warn};
@@ -33,6 +33,24 @@ impl Profile {
                 param.attr_handle
             );
             descriptor.write().unwrap().attribute_handle = Some(param.attr_handle);
+
+            if descriptor.read().unwrap().uuid == BleUuid::Uuid16(0x2902) {
+                if let Some(path) = self.attribute_path(&service, descriptor) {
+                    register_cccd_path(param.attr_handle, &path);
+                }
+            }
+
+            if descriptor.read().unwrap().uuid == BleUuid::Uuid16(0x2903) {
+                if let Some(characteristic_handle) = Self::characteristic_of(&service, descriptor)
+                    .and_then(|characteristic| characteristic.read().unwrap().attribute_handle)
+                {
+                    register_sccd(param.attr_handle, characteristic_handle);
+                }
+            }
+
+            if descriptor.read().unwrap().uuid == BleUuid::Uuid16(0x2905) {
+                Self::fill_aggregate_format(&service, descriptor);
+            }
         } else {
             warn!("GATT descriptor registration failed.");
         }
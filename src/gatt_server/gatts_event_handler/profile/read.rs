@@ -1,5 +1,6 @@
-use crate::gatt_server::Profile;
-use crate::utilities::AttributeControl;
+use crate::gatt_server::authorization::Authorizer;
+use crate::gatt_server::{Access, GattServer, Profile};
+use crate::utilities::Connection;
 use esp_idf_sys::*;
 use log::debug;
 
@@ -8,6 +9,7 @@ impl Profile {
         &mut self,
         gatts_if: esp_gatt_if_t,
         param: esp_ble_gatts_cb_param_t_gatts_read_evt_param,
+        connection: Option<&Connection>,
     ) {
         for service in &self.services {
             service
@@ -16,96 +18,96 @@ impl Profile {
                 .characteristics
                 .iter()
                 .for_each(|characteristic| {
-                    if characteristic.read().unwrap().attribute_handle == Some(param.handle) {
-                        debug!(
-                            "Received read event for characteristic {}.",
-                            characteristic.read().unwrap()
-                        );
+                    let characteristic = characteristic.read().unwrap();
 
-                        // If the characteristic has a read handler, call it.
-                        if let AttributeControl::ResponseByApp(callback) =
-                            &characteristic.read().unwrap().control
-                        {
-                            let value = callback(param);
+                    if characteristic.attribute_handle == Some(param.handle) {
+                        debug!("Received read event for characteristic {}.", characteristic);
 
-                            // Extend the response to the maximum length.
-                            let mut response = [0u8; 600];
-                            response[..value.len()].copy_from_slice(&value);
+                        // Check the permissions and respond, if needed.
+                        if characteristic.responds_by_app() {
+                            let result = characteristic
+                                .permissions
+                                .check_read(connection)
+                                .and_then(|()| {
+                                    Authorizer::check(
+                                        characteristic.authorizer.as_ref(),
+                                        connection,
+                                        characteristic.uuid,
+                                        param.handle,
+                                        Access::Read,
+                                    )
+                                })
+                                .and_then(|()| {
+                                    value_from_offset(
+                                        characteristic.read_value(param),
+                                        param.offset,
+                                    )
+                                });
 
-                            let mut esp_rsp = esp_gatt_rsp_t {
-                                attr_value: esp_gatt_value_t {
-                                    auth_req: 0,
-                                    handle: param.handle,
-                                    len: value.len() as u16,
-                                    offset: 0,
-                                    value: response,
-                                },
-                            };
-
-                            unsafe {
-                                esp_nofail!(esp_ble_gatts_send_response(
-                                    gatts_if,
-                                    param.conn_id,
-                                    param.trans_id,
-                                    // TODO: Allow different statuses.
-                                    esp_gatt_status_t_ESP_GATT_OK,
-                                    &mut esp_rsp
-                                ));
-                            }
+                            GattServer::send_response(
+                                gatts_if,
+                                param.conn_id,
+                                param.trans_id,
+                                param.handle,
+                                0,
+                                result,
+                            );
                         }
                     } else {
-                        characteristic
-                            .read()
-                            .unwrap()
-                            .descriptors
-                            .iter()
-                            .for_each(|descriptor| {
-                                debug!(
-                                    "MCC: Checking descriptor {} ({:?}).",
-                                    descriptor.read().unwrap(),
-                                    descriptor.read().unwrap().attribute_handle
-                                );
-
-                                if descriptor.read().unwrap().attribute_handle == Some(param.handle)
-                                {
-                                    debug!(
-                                        "Received read event for descriptor {}.",
-                                        descriptor.read().unwrap()
-                                    );
+                        characteristic.descriptors.iter().for_each(|descriptor| {
+                            let descriptor = descriptor.read().unwrap();
 
-                                    if let AttributeControl::ResponseByApp(callback) =
-                                        &descriptor.read().unwrap().control
-                                    {
-                                        let value = callback(param);
+                            debug!(
+                                "MCC: Checking descriptor {} ({:?}).",
+                                descriptor, descriptor.attribute_handle
+                            );
 
-                                        // Extend the response to the maximum length.
-                                        let mut response = [0u8; 600];
-                                        response[..value.len()].copy_from_slice(&value);
+                            if descriptor.attribute_handle == Some(param.handle) {
+                                debug!("Received read event for descriptor {}.", descriptor);
 
-                                        let mut esp_rsp = esp_gatt_rsp_t {
-                                            attr_value: esp_gatt_value_t {
-                                                auth_req: 0,
-                                                handle: param.handle,
-                                                len: value.len() as u16,
-                                                offset: 0,
-                                                value: response,
-                                            },
-                                        };
+                                if descriptor.responds_by_app() {
+                                    let result = descriptor
+                                        .permissions
+                                        .check_read(connection)
+                                        .and_then(|()| {
+                                            Authorizer::check(
+                                                descriptor.authorizer.as_ref(),
+                                                connection,
+                                                descriptor.uuid,
+                                                param.handle,
+                                                Access::Read,
+                                            )
+                                        })
+                                        .and_then(|()| {
+                                            value_from_offset(
+                                                descriptor.read_value(param),
+                                                param.offset,
+                                            )
+                                        });
 
-                                        unsafe {
-                                            esp_nofail!(esp_ble_gatts_send_response(
-                                                gatts_if,
-                                                param.conn_id,
-                                                param.trans_id,
-                                                esp_gatt_status_t_ESP_GATT_OK,
-                                                &mut esp_rsp
-                                            ));
-                                        }
-                                    }
+                                    GattServer::send_response(
+                                        gatts_if,
+                                        param.conn_id,
+                                        param.trans_id,
+                                        param.handle,
+                                        0,
+                                        result,
+                                    );
                                 }
-                            });
+                            }
+                        });
                     }
                 });
         }
     }
 }
+
+/// Returns the part of a value that a client reads, starting at the offset of the request.
+///
+/// Clients read the values that do not fit in a response with several requests, at increasing offsets.
+fn value_from_offset(value: Vec<u8>, offset: u16) -> Result<Vec<u8>, esp_gatt_status_t> {
+    value
+        .get(usize::from(offset)..)
+        .map(<[u8]>::to_vec)
+        .ok_or(esp_gatt_status_t_ESP_GATT_INVALID_OFFSET)
+}
@@ -1,161 +1,435 @@
-use crate::gatt_server::Profile;
-use crate::utilities::AttributeControl;
+use std::sync::{Arc, RwLock};
+
+use crate::gatt_server::authorization::Authorizer;
+use crate::gatt_server::profile::PreparedWrite;
+use crate::gatt_server::{Access, Characteristic, Descriptor, GattServer, Profile};
+use crate::utilities::{AttributeControl, Connection};
 use esp_idf_sys::*;
-use log::debug;
+use log::{debug, warn};
+
+/// The maximum number of bytes that a client can queue with prepared writes to a profile.
+const MAX_PREPARED_LENGTH: usize = 2048;
+
+/// An attribute written by a client.
+enum WriteTarget {
+    Characteristic(Arc<RwLock<Characteristic>>),
+    Descriptor(Arc<RwLock<Descriptor>>),
+}
+
+impl WriteTarget {
+    /// Returns `true` if the write requests to this attribute are answered by the library.
+    fn responds_by_app(&self) -> bool {
+        match self {
+            Self::Characteristic(characteristic) => {
+                characteristic.read().unwrap().responds_by_app()
+            }
+            Self::Descriptor(descriptor) => descriptor.read().unwrap().responds_by_app(),
+        }
+    }
+
+    /// Checks the permissions that do not depend on the written value.
+    fn check_permissions(&self, connection: Option<&Connection>) -> Result<(), esp_gatt_status_t> {
+        match self {
+            Self::Characteristic(characteristic) => characteristic
+                .read()
+                .unwrap()
+                .permissions
+                .check_write(connection),
+            Self::Descriptor(descriptor) => descriptor
+                .read()
+                .unwrap()
+                .permissions
+                .check_write(connection),
+        }
+    }
+
+    /// Checks whether a complete value can be written by a client.
+    fn check(
+        &self,
+        value: &[u8],
+        connection: Option<&Connection>,
+        handle: u16,
+    ) -> Result<(), esp_gatt_status_t> {
+        self.check_permissions(connection)?;
+
+        match self {
+            Self::Characteristic(characteristic) => {
+                let characteristic = characteristic.read().unwrap();
+
+                Authorizer::check(
+                    characteristic.authorizer.as_ref(),
+                    connection,
+                    characteristic.uuid,
+                    handle,
+                    Access::Write,
+                )?;
+
+                characteristic.validate(value).map_err(|status| {
+                    warn!(
+                        "Rejecting invalid value {:02X?} for characteristic {}.",
+                        value, characteristic
+                    );
+                    status
+                })?;
+
+                if matches!(
+                    characteristic.control,
+                    AttributeControl::AutomaticResponse(_)
+                ) && value.len() > characteristic.value_capacity()
+                {
+                    return Err(esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN);
+                }
+            }
+            Self::Descriptor(descriptor) => {
+                let descriptor = descriptor.read().unwrap();
+
+                Authorizer::check(
+                    descriptor.authorizer.as_ref(),
+                    connection,
+                    descriptor.uuid,
+                    handle,
+                    Access::Write,
+                )?;
+
+                if matches!(descriptor.control, AttributeControl::AutomaticResponse(_))
+                    && value.len() > descriptor.value_capacity()
+                {
+                    return Err(esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies a value written by a client, once it has been checked.
+    fn apply(&self, value: &[u8], param: esp_ble_gatts_cb_param_t_gatts_write_evt_param) {
+        let responds_by_app = self.responds_by_app();
+
+        match self {
+            Self::Characteristic(characteristic_lock) => {
+                // The stack does not store values written to these attributes.
+                // The value is stored directly, because `set_value` would also update the stack
+                // and the broadcasts, as for values set by the application.
+                if responds_by_app {
+                    let mut characteristic = characteristic_lock.write().unwrap();
+
+                    if let AttributeControl::AutomaticResponse(_) = characteristic.control {
+                        characteristic.internal_value = value.to_vec();
+                        characteristic.control =
+                            AttributeControl::AutomaticResponse(value.to_vec());
+                    }
+                }
+
+                let characteristic = characteristic_lock.read().unwrap().clone();
+
+                // Persisted values are always complete, because they are answered by the library.
+                if let Some(persistence) = &characteristic.persistence {
+                    persistence.store(value.to_vec());
+                }
+
+                // If the characteristic has a write handler, call it.
+                if let Some(write_callback) = &characteristic.write_callback {
+                    write_callback(value.to_vec(), param);
+                }
+            }
+            Self::Descriptor(descriptor_lock) => {
+                // The stack does not store values written to these attributes.
+                if responds_by_app {
+                    let mut descriptor = descriptor_lock.write().unwrap();
+
+                    if let AttributeControl::AutomaticResponse(_) = descriptor.control {
+                        descriptor.value = value.to_vec();
+                        descriptor.control = AttributeControl::AutomaticResponse(value.to_vec());
+                    }
+                }
+
+                let write_callback = descriptor_lock.read().unwrap().write_callback;
+
+                if let Some(write_callback) = write_callback {
+                    write_callback(value.to_vec(), param);
+                }
+            }
+        }
+    }
+
+    /// Returns the value that prepared writes are applied to.
+    ///
+    /// The values of the attributes with a read callback are not known, so they are rewritten from scratch.
+    fn stored_value(&self) -> Vec<u8> {
+        let param = esp_ble_gatts_cb_param_t_gatts_read_evt_param::default();
+
+        match self {
+            Self::Characteristic(characteristic) => {
+                let characteristic = characteristic.read().unwrap();
+
+                match characteristic.control {
+                    AttributeControl::AutomaticResponse(_) => characteristic.read_value(param),
+                    AttributeControl::ResponseByApp(_) => Vec::new(),
+                }
+            }
+            Self::Descriptor(descriptor) => {
+                let descriptor = descriptor.read().unwrap();
+
+                match descriptor.control {
+                    AttributeControl::AutomaticResponse(_) => descriptor.read_value(param),
+                    AttributeControl::ResponseByApp(_) => Vec::new(),
+                }
+            }
+        }
+    }
+
+    /// Returns the current value of the attribute, to answer a write request.
+    fn read_value(&self, param: esp_ble_gatts_cb_param_t_gatts_read_evt_param) -> Vec<u8> {
+        match self {
+            Self::Characteristic(characteristic) => {
+                characteristic.read().unwrap().read_value(param)
+            }
+            Self::Descriptor(descriptor) => descriptor.read().unwrap().read_value(param),
+        }
+    }
+}
+
+impl std::fmt::Display for WriteTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Characteristic(characteristic) => {
+                write!(f, "characteristic {}", characteristic.read().unwrap())
+            }
+            Self::Descriptor(descriptor) => {
+                write!(f, "descriptor {}", descriptor.read().unwrap())
+            }
+        }
+    }
+}
 
 impl Profile {
-    #[allow(clippy::too_many_lines)]
     pub(crate) fn on_write(
         &mut self,
         gatts_if: esp_gatt_if_t,
         param: esp_ble_gatts_cb_param_t_gatts_write_evt_param,
+        connection: Option<&Connection>,
+    ) {
+        let Some(target) = self.write_target(param.handle) else {
+            return;
+        };
+
+        debug!("Received write event for {}.", target);
+
+        let value = unsafe { std::slice::from_raw_parts(param.value, param.len as usize) }.to_vec();
+
+        // The stack has already stored the value and answered the request.
+        if !target.responds_by_app() {
+            target.apply(&value, param);
+            return;
+        }
+
+        if param.is_prep {
+            let result = self.prepare_write(&target, param, connection, &value);
+
+            // The client checks that the prepared value is echoed back.
+            if param.need_rsp {
+                GattServer::send_response(
+                    gatts_if,
+                    param.conn_id,
+                    param.trans_id,
+                    param.handle,
+                    param.offset,
+                    result.map(|()| value),
+                );
+            }
+
+            return;
+        }
+
+        let result = target.check(&value, connection, param.handle).map(|()| {
+            target.apply(&value, param);
+
+            // Simulate a read operation, used to answer the write request.
+            target.read_value(esp_ble_gatts_cb_param_t_gatts_read_evt_param {
+                bda: param.bda,
+                conn_id: param.conn_id,
+                handle: param.handle,
+                need_rsp: param.need_rsp,
+                trans_id: param.trans_id,
+                ..Default::default()
+            })
+        });
+
+        if let Err(status) = result {
+            warn!("Rejecting write to {}: status 0x{:02X}.", target, status);
+        }
+
+        if param.need_rsp {
+            GattServer::send_response(
+                gatts_if,
+                param.conn_id,
+                param.trans_id,
+                param.handle,
+                0,
+                result,
+            );
+        }
+    }
+
+    /// Executes or cancels the writes prepared by a client.
+    ///
+    /// The values of the attributes are assembled from the prepared writes, in order,
+    /// and are only checked and applied once complete. If any value is rejected, none is applied.
+    pub(crate) fn on_exec_write(
+        &mut self,
+        gatts_if: esp_gatt_if_t,
+        param: esp_ble_gatts_cb_param_t_gatts_exec_write_evt_param,
+        connection: Option<&Connection>,
     ) {
-        for service in &self.services {
+        let (prepared_writes, other_writes) = std::mem::take(&mut self.prepared_writes)
+            .into_iter()
+            .partition(|write| write.conn_id == param.conn_id);
+        self.prepared_writes = other_writes;
+
+        let result = if u32::from(param.exec_write_flag) == ESP_GATT_PREP_WRITE_EXEC {
+            self.execute_writes(prepared_writes, param, connection)
+        } else {
+            debug!("Prepared writes cancelled by the client.");
+            Ok(())
+        };
+
+        if let Err(status) = result {
+            warn!("Rejecting prepared writes: status 0x{:02X}.", status);
+        }
+
+        GattServer::send_response(
+            gatts_if,
+            param.conn_id,
+            param.trans_id,
+            0,
+            0,
+            result.map(|()| Vec::new()),
+        );
+    }
+
+    /// Forgets the writes prepared by a client that disconnected.
+    pub(crate) fn forget_prepared_writes(&mut self, conn_id: u16) {
+        self.prepared_writes
+            .retain(|write| write.conn_id != conn_id);
+    }
+
+    /// Queues a part of a value, to be written when the client executes the prepared writes.
+    fn prepare_write(
+        &mut self,
+        target: &WriteTarget,
+        param: esp_ble_gatts_cb_param_t_gatts_write_evt_param,
+        connection: Option<&Connection>,
+        value: &[u8],
+    ) -> Result<(), esp_gatt_status_t> {
+        target.check_permissions(connection)?;
+
+        let queued: usize = self
+            .prepared_writes
+            .iter()
+            .filter(|write| write.conn_id == param.conn_id)
+            .map(|write| write.value.len())
+            .sum();
+
+        if queued + value.len() > MAX_PREPARED_LENGTH {
+            return Err(esp_gatt_status_t_ESP_GATT_PREPARE_Q_FULL);
+        }
+
+        self.prepared_writes.push(PreparedWrite {
+            conn_id: param.conn_id,
+            handle: param.handle,
+            offset: param.offset,
+            value: value.to_vec(),
+        });
+
+        Ok(())
+    }
+
+    /// Assembles, checks and applies the values written with prepared writes.
+    fn execute_writes(
+        &self,
+        prepared_writes: Vec<PreparedWrite>,
+        param: esp_ble_gatts_cb_param_t_gatts_exec_write_evt_param,
+        connection: Option<&Connection>,
+    ) -> Result<(), esp_gatt_status_t> {
+        let mut values: Vec<(WriteTarget, u16, Vec<u8>)> = Vec::new();
+
+        for write in prepared_writes {
+            let index = if let Some(index) = values
+                .iter()
+                .position(|(_, handle, _)| *handle == write.handle)
+            {
+                index
+            } else {
+                let target = self
+                    .write_target(write.handle)
+                    .ok_or(esp_gatt_status_t_ESP_GATT_INVALID_HANDLE)?;
+                let value = target.stored_value();
+                values.push((target, write.handle, value));
+                values.len() - 1
+            };
+
+            // Each part replaces the end of the value, starting at its offset.
+            let value = &mut values[index].2;
+            let offset = usize::from(write.offset);
+
+            if offset > value.len() {
+                return Err(esp_gatt_status_t_ESP_GATT_INVALID_OFFSET);
+            }
+
+            value.truncate(offset);
+            value.extend_from_slice(&write.value);
+        }
+
+        for (target, handle, value) in &values {
+            target.check(value, connection, *handle)?;
+        }
+
+        for (target, handle, mut value) in values {
+            debug!("Writing {:02X?} to {}.", value, target);
+
+            #[allow(clippy::cast_possible_truncation)]
+            let write_param = esp_ble_gatts_cb_param_t_gatts_write_evt_param {
+                conn_id: param.conn_id,
+                trans_id: param.trans_id,
+                bda: param.bda,
+                handle,
+                offset: 0,
+                need_rsp: false,
+                is_prep: false,
+                len: value.len() as u16,
+                value: value.as_mut_ptr(),
+            };
+
+            target.apply(&value, write_param);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the characteristic or the descriptor with the given handle.
+    fn write_target(&self, handle: u16) -> Option<WriteTarget> {
+        self.services.iter().find_map(|service| {
             service
                 .read()
                 .unwrap()
                 .characteristics
                 .iter()
-                .for_each(|characteristic| {
-                    if characteristic.read().unwrap().attribute_handle == Some(param.handle) {
-                        debug!(
-                            "Received write event for characteristic {}.",
-                            characteristic.read().unwrap()
-                        );
-
-                        // If the characteristic has a write handler, call it.
-                        if let Some(write_callback) = &characteristic.read().unwrap().write_callback
-                        {
-                            let value = unsafe {
-                                std::slice::from_raw_parts(param.value, param.len as usize)
-                            }
-                            .to_vec();
-
-                            write_callback(value, param);
-
-                            // Send response if needed.
-                            if param.need_rsp {
-                                if let AttributeControl::ResponseByApp(read_callback) =
-                                    &characteristic.read().unwrap().control
-                                {
-                                    // Simulate a read operation.
-                                    let param_as_read_operation =
-                                        esp_ble_gatts_cb_param_t_gatts_read_evt_param {
-                                            bda: param.bda,
-                                            conn_id: param.conn_id,
-                                            handle: param.handle,
-                                            need_rsp: param.need_rsp,
-                                            offset: param.offset,
-                                            trans_id: param.trans_id,
-                                            ..Default::default()
-                                        };
-
-                                    // Get value.
-                                    let value = read_callback(param_as_read_operation);
-
-                                    // Extend the response to the maximum length.
-                                    let mut response = [0u8; 600];
-                                    response[..value.len()].copy_from_slice(&value);
-
-                                    let mut esp_rsp = esp_gatt_rsp_t {
-                                        attr_value: esp_gatt_value_t {
-                                            auth_req: 0,
-                                            handle: param.handle,
-                                            len: value.len() as u16,
-                                            offset: 0,
-                                            value: response,
-                                        },
-                                    };
-
-                                    unsafe {
-                                        esp_nofail!(esp_ble_gatts_send_response(
-                                            gatts_if,
-                                            param.conn_id,
-                                            param.trans_id,
-                                            esp_gatt_status_t_ESP_GATT_OK,
-                                            &mut esp_rsp
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        characteristic
-                            .read()
-                            .unwrap()
-                            .descriptors
-                            .iter()
-                            .for_each(|descriptor| {
-                                if descriptor.read().unwrap().attribute_handle == Some(param.handle)
-                                {
-                                    debug!(
-                                        "Received write event for descriptor {}.",
-                                        descriptor.read().unwrap()
-                                    );
-
-                                    if let Some(write_callback) =
-                                        descriptor.read().unwrap().write_callback
-                                    {
-                                        let value = unsafe {
-                                            std::slice::from_raw_parts(
-                                                param.value,
-                                                param.len as usize,
-                                            )
-                                        }
-                                        .to_vec();
-
-                                        write_callback(value, param);
-
-                                        // Send response if needed.
-                                        if param.need_rsp {
-                                            if let AttributeControl::ResponseByApp(read_callback) =
-                                                &descriptor.read().unwrap().control
-                                            {
-                                                // Simulate a read operation.
-                                                let param_as_read_operation =
-                                                    esp_ble_gatts_cb_param_t_gatts_read_evt_param {
-                                                        bda: param.bda,
-                                                        conn_id: param.conn_id,
-                                                        handle: param.handle,
-                                                        need_rsp: param.need_rsp,
-                                                        offset: param.offset,
-                                                        trans_id: param.trans_id,
-                                                        ..Default::default()
-                                                    };
-
-                                                // Get value.
-                                                let value = read_callback(param_as_read_operation);
-
-                                                // Extend the response to the maximum length.
-                                                let mut response = [0u8; 600];
-                                                response[..value.len()].copy_from_slice(&value);
-
-                                                let mut esp_rsp = esp_gatt_rsp_t {
-                                                    attr_value: esp_gatt_value_t {
-                                                        auth_req: 0,
-                                                        handle: param.handle,
-                                                        len: value.len() as u16,
-                                                        offset: 0,
-                                                        value: response,
-                                                    },
-                                                };
-
-                                                unsafe {
-                                                    esp_nofail!(esp_ble_gatts_send_response(
-                                                        gatts_if,
-                                                        param.conn_id,
-                                                        param.trans_id,
-                                                        esp_gatt_status_t_ESP_GATT_OK,
-                                                        &mut esp_rsp
-                                                    ));
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            });
+                .find_map(|characteristic| {
+                    if characteristic.read().unwrap().attribute_handle == Some(handle) {
+                        return Some(WriteTarget::Characteristic(characteristic.clone()));
                     }
-                });
-        }
+
+                    characteristic
+                        .read()
+                        .unwrap()
+                        .descriptors
+                        .iter()
+                        .find(|descriptor| {
+                            descriptor.read().unwrap().attribute_handle == Some(handle)
+                        })
+                        .map(|descriptor| WriteTarget::Descriptor(descriptor.clone()))
+                })
+        })
     }
 }
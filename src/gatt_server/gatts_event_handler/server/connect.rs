@@ -7,7 +7,9 @@ impl GattServer {
         &mut self,
         param: esp_idf_sys::esp_ble_gatts_cb_param_t_gatts_connect_evt_param,
     ) {
-        info!("GATT client {} connected.", Connection::from(param));
-        self.active_connections.insert(param.into());
+        let connection = Connection::from(param);
+        info!("GATT client {} connected.", connection);
+        self.active_connections.insert(connection);
+        self.request_encryption(connection);
     }
 }
@@ -1,4 +1,7 @@
-use crate::gatt_server::GattServer;
+use crate::{
+    gatt_server::{cccd::forget_cccds, GattServer},
+    utilities::PENDING_DATA_LENGTH_REQUEST,
+};
 use log::info;
 
 impl GattServer {
@@ -12,9 +15,23 @@ impl GattServer {
         );
 
         self.active_connections.remove(&param.into());
+        forget_cccds(param.remote_bda);
 
-        unsafe {
-            esp_idf_sys::esp_ble_gap_start_advertising(&mut self.advertisement_parameters);
+        // The data length request of a disconnected device may never complete.
+        let mut pending_data_length_request = PENDING_DATA_LENGTH_REQUEST.lock().unwrap();
+        if *pending_data_length_request == Some(param.remote_bda) {
+            *pending_data_length_request = None;
         }
+        drop(pending_data_length_request);
+
+        for profile in &self.profiles {
+            profile
+                .write()
+                .unwrap()
+                .forget_prepared_writes(param.conn_id);
+        }
+
+        // Connectable advertising only restarts once the last client has disconnected.
+        self.restart_advertising();
     }
 }
@@ -1,12 +1,27 @@
 use crate::gatt_server::GattServer;
-use log::debug;
+use log::{debug, warn};
 
 impl GattServer {
-    #[allow(clippy::unused_self)]
     pub(crate) fn on_mtu_change(
-        &self,
+        &mut self,
         param: esp_idf_sys::esp_ble_gatts_cb_param_t_gatts_mtu_evt_param,
     ) {
-        debug!("MTU changed to {}.", param.mtu);
+        let Some(mut connection) = self
+            .active_connections
+            .iter()
+            .find(|connection| connection.id == param.conn_id)
+            .copied()
+        else {
+            warn!(
+                "MTU changed to {} for unknown connection {}.",
+                param.mtu, param.conn_id
+            );
+            return;
+        };
+
+        debug!("MTU of {} changed to {}.", connection, param.mtu);
+
+        connection.mtu = param.mtu;
+        self.active_connections.replace(connection);
     }
 }
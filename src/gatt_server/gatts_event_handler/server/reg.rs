@@ -25,15 +25,12 @@ impl GattServer {
                     esp_nofail!(esp_ble_gap_set_device_name(
                         self.device_name.as_ptr().cast::<i8>()
                     ));
+                }
 
-                    self.advertisement_configured = true;
-
-                    // Advertisement data.
-                    esp_nofail!(esp_ble_gap_config_adv_data(&mut self.advertisement_data));
+                self.advertisement_configured = true;
 
-                    // Scan response data.
-                    esp_nofail!(esp_ble_gap_config_adv_data(&mut self.scan_response_data));
-                }
+                // The advertisement data is configured once the local address is set.
+                self.configure_local_address();
             }
         }
     }
@@ -1,4 +1,5 @@
 use crate::gatt_server::GattServer;
+use esp_idf_sys::*;
 use log::debug;
 
 impl GattServer {
@@ -9,4 +10,47 @@ impl GattServer {
     ) {
         debug!("Responded to handle 0x{:04x}.", param.handle);
     }
+
+    /// Answers a read or write request, either with a value or with an error status.
+    ///
+    /// The offset is only echoed back in the answers to prepared writes.
+    pub(crate) fn send_response(
+        gatts_if: esp_gatt_if_t,
+        conn_id: u16,
+        trans_id: u32,
+        handle: u16,
+        offset: u16,
+        result: Result<Vec<u8>, esp_gatt_status_t>,
+    ) {
+        let (status, value) = match result {
+            Ok(value) => (esp_gatt_status_t_ESP_GATT_OK, value),
+            Err(status) => (status, Vec::new()),
+        };
+
+        // Extend the response to the maximum length.
+        let mut response = [0u8; 600];
+        let length = value.len().min(response.len());
+        response[..length].copy_from_slice(&value[..length]);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut esp_rsp = esp_gatt_rsp_t {
+            attr_value: esp_gatt_value_t {
+                auth_req: 0,
+                handle,
+                len: length as u16,
+                offset,
+                value: response,
+            },
+        };
+
+        unsafe {
+            esp_nofail!(esp_ble_gatts_send_response(
+                gatts_if,
+                conn_id,
+                trans_id,
+                status,
+                &mut esp_rsp
+            ));
+        }
+    }
 }
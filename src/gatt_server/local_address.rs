@@ -0,0 +1,207 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use esp_idf_sys::*;
+use log::{debug, info, warn};
+
+use crate::{
+    gatt_server::{GattServer, GLOBAL_GATT_SERVER},
+    utilities::LocalAddress,
+};
+
+/// The storage key under which the generated random static address is stored.
+const RANDOM_STATIC_ADDRESS_KEY: &str = "static_addr";
+
+/// How often the address used by the stack is checked for rotations.
+const LOCAL_ADDRESS_POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether the thread that checks for address rotations is running.
+static LOCAL_ADDRESS_MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+
+impl GattServer {
+    /// Sets the address that the device uses to identify itself over the air.
+    ///
+    /// The address must be set before starting the server.
+    pub fn local_address(&mut self, address: LocalAddress) -> &mut Self {
+        if self.started {
+            warn!("Cannot change the local address after the server has started.");
+            return self;
+        }
+
+        if let LocalAddress::CustomRandomStatic(custom_address) = address {
+            if !LocalAddress::is_random_static(custom_address) {
+                warn!(
+                    "{:02X?} is not a valid random static address. Ignoring local address.",
+                    custom_address
+                );
+                return self;
+            }
+        }
+
+        self.local_address = address;
+        self.advertisement_parameters.own_addr_type = address.into();
+
+        for advertisement in &self.extended_advertisements {
+            self.apply_own_address_type(&mut advertisement.write().unwrap());
+        }
+
+        self
+    }
+
+    /// Sets a callback that is called when the address used by the device changes.
+    ///
+    /// The callback receives the new address, and is called once when advertising starts for the first time,
+    /// then every time a resolvable private address is rotated.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_local_address_change(
+        &mut self,
+        callback: impl Fn([u8; 6]) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.local_address_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Configures the local address in the stack.
+    ///
+    /// The advertisement data is configured as soon as the stack acknowledges the new address.
+    pub(crate) fn configure_local_address(&mut self) {
+        debug!("Configuring local address {:?}.", self.local_address);
+
+        match self.local_address {
+            LocalAddress::Public => self.configure_advertisement_data(),
+            LocalAddress::RandomStatic => {
+                let mut address = Self::random_static_address();
+                unsafe {
+                    esp_nofail!(esp_ble_gap_set_rand_addr(address.as_mut_ptr()));
+                }
+            }
+            LocalAddress::CustomRandomStatic(mut address) => unsafe {
+                esp_nofail!(esp_ble_gap_set_rand_addr(address.as_mut_ptr()));
+            },
+            LocalAddress::ResolvablePrivate(_) => {
+                // The identity key is distributed by `configure_security`.
+                #[cfg(not(esp_idf_version_major = "4"))]
+                if let Some(timeout) = self.local_address.rotation_timeout() {
+                    unsafe {
+                        esp_nofail!(esp_ble_gap_set_resolvable_private_address_timeout(timeout));
+                    }
+                }
+
+                #[cfg(esp_idf_version_major = "4")]
+                warn!(
+                    "Custom RPA rotation intervals require ESP-IDF 5. Using the default interval."
+                );
+
+                unsafe {
+                    esp_nofail!(esp_ble_gap_config_local_privacy(true));
+                }
+            }
+        }
+    }
+
+    /// Checks whether the address used by the stack has changed, and calls the user callback if so.
+    pub(crate) fn check_local_address_change(&mut self) {
+        let mut address = [0u8; 6];
+        let mut address_type = 0u8;
+
+        let result = unsafe {
+            esp!(esp_ble_gap_get_local_used_addr(
+                address.as_mut_ptr(),
+                &mut address_type
+            ))
+        };
+
+        if let Err(error) = result {
+            warn!("Cannot read the local address: {}.", error);
+            return;
+        }
+
+        if self.current_local_address == Some(address) {
+            return;
+        }
+
+        info!("Local address changed to {:02X?}.", address);
+        self.current_local_address = Some(address);
+
+        if let Some(callback) = &self.local_address_callback {
+            callback(address);
+        }
+    }
+
+    /// Returns the random address that the advertising sets must use, if any.
+    #[cfg(esp_idf_bt_ble_50_features_supported)]
+    pub(crate) fn random_address(&self) -> Option<[u8; 6]> {
+        match self.local_address {
+            LocalAddress::RandomStatic => Some(Self::random_static_address()),
+            LocalAddress::CustomRandomStatic(address) => Some(address),
+            LocalAddress::Public | LocalAddress::ResolvablePrivate(_) => None,
+        }
+    }
+
+    /// Periodically checks for resolvable private address rotations.
+    ///
+    /// The stack rotates the address internally and does not notify the application, so we need to poll.
+    /// A single thread is started, however many times privacy is configured, and it stops
+    /// when the device no longer uses resolvable private addresses.
+    pub(crate) fn spawn_local_address_monitor() {
+        if LOCAL_ADDRESS_MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+            debug!("Local address monitor already running.");
+            return;
+        }
+
+        std::thread::spawn(|| loop {
+            std::thread::sleep(LOCAL_ADDRESS_POLLING_INTERVAL);
+
+            let mut server = GLOBAL_GATT_SERVER
+                .lock()
+                .expect("Cannot lock global GATT server.");
+
+            // The flag is cleared while holding the lock, so that no new monitor can be missed.
+            if !matches!(server.local_address, LocalAddress::ResolvablePrivate(_)) {
+                debug!("Stopping local address monitor.");
+                LOCAL_ADDRESS_MONITOR_RUNNING.store(false, Ordering::SeqCst);
+                break;
+            }
+
+            server.check_local_address_change();
+        });
+    }
+
+    /// Returns the stored random static address, or generates and stores a new one.
+    fn random_static_address() -> [u8; 6] {
+        let stored = Self::with_storage(|storage| storage.get(RANDOM_STATIC_ADDRESS_KEY))
+            .ok()
+            .flatten()
+            .and_then(|value| <[u8; 6]>::try_from(value).ok());
+
+        if let Some(address) = stored.filter(|address| LocalAddress::is_random_static(*address)) {
+            debug!("Using stored random static address {:02X?}.", address);
+            return address;
+        }
+
+        let mut address = [0u8; 6];
+        unsafe {
+            esp_fill_random(address.as_mut_ptr().cast(), address.len() as u32);
+        }
+
+        // The two most significant bits of a random static address must be set.
+        address[0] |= 0b1100_0000;
+
+        if let Err(error) =
+            Self::with_storage(|storage| storage.set(RANDOM_STATIC_ADDRESS_KEY, &address))
+        {
+            warn!("Cannot store the random static address: {}.", error);
+        }
+
+        info!("Generated new random static address {:02X?}.", address);
+        address
+    }
+}
@@ -9,26 +9,62 @@ use std::{
 
 use esp_idf_sys::*;
 use lazy_static::lazy_static;
-use log::{info, warn};
+use log::{debug, info, warn};
 
 use crate::{
+    gatt_server::{
+        authorization::Authorizer, encryption::EncryptionRequirement, oob::OobCallbacks,
+        pairing::PairingCallbacks,
+    },
     leaky_box_raw,
-    utilities::{Appearance, Connection},
+    utilities::{
+        Appearance, Connection, ConnectionParameters, LocalAddress, SecurityConfig, TxPowerLevel,
+        TxPowerType,
+    },
 };
 
+pub use authorization::{Access, AccessRequest};
+pub use bonding::AuthenticationResult;
 pub use characteristic::Characteristic;
 pub use descriptor::Descriptor;
+#[cfg(not(esp_idf_bt_ble_50_features_supported))]
+pub(crate) use extended_advertisement::not_supported;
+pub use extended_advertisement::ExtendedAdvertisement;
+pub use oob::{OobData, OobRequest, ScOobRequest};
+pub use pairing::{NumericComparison, PasskeyRequest, SecurityRequest};
+pub use periodic_advertiser::{PeriodicAdvertiser, PeriodicAdvertisingStatus};
 pub use profile::Profile;
+pub use rssi_monitor::{Proximity, RssiMonitor};
 pub use service::Service;
+pub use typed_characteristic::TypedCharacteristic;
 
 // Structs.
 mod characteristic;
 mod descriptor;
+mod extended_advertisement;
+mod periodic_advertiser;
 mod profile;
+mod rssi_monitor;
 mod service;
+mod typed_characteristic;
 
 // Custom stuff.
+mod authorization;
+mod bonding;
+mod broadcast;
+mod cccd;
+mod connections;
+mod constraints;
 mod custom_attributes;
+mod encryption;
+mod local_address;
+mod notifications;
+mod oob;
+mod pairing;
+mod persistence;
+mod security;
+mod storage;
+mod tx_power;
 
 // Event handler.
 mod gap_event_handler;
@@ -43,7 +79,7 @@ lazy_static! {
             adv_int_min: 0x20,
             adv_int_max: 0x40,
             adv_type: esp_ble_adv_type_t_ADV_TYPE_IND,
-            own_addr_type: esp_ble_addr_type_t_BLE_ADDR_TYPE_RPA_PUBLIC,
+            own_addr_type: esp_ble_addr_type_t_BLE_ADDR_TYPE_PUBLIC,
             channel_map: esp_ble_adv_channel_t_ADV_CHNL_ALL,
             adv_filter_policy: esp_ble_adv_filter_t_ADV_FILTER_ALLOW_SCAN_ANY_CON_ANY,
             ..Default::default()
@@ -81,6 +117,20 @@ lazy_static! {
         advertisement_configured: false,
         device_name: "ESP32".to_string(),
         active_connections: HashSet::new(),
+        local_address: LocalAddress::Public,
+        current_local_address: None,
+        local_address_callback: None,
+        extended_advertisements: Vec::new(),
+        periodic_advertisers: Vec::new(),
+        tx_power_levels: Vec::new(),
+        connection_parameters_callback: None,
+        security_config: SecurityConfig::new(),
+        pairing_callbacks: PairingCallbacks::default(),
+        authentication_callback: None,
+        authorizer: None,
+        encryption_requirement: None,
+        oob_callbacks: OobCallbacks::default(),
+        erase_nvs_on_failure: true,
     });
 }
 
@@ -96,6 +146,23 @@ pub struct GattServer {
     device_name: String,
     advertisement_configured: bool,
     active_connections: HashSet<Connection>,
+    local_address: LocalAddress,
+    current_local_address: Option<[u8; 6]>,
+    local_address_callback: Option<Arc<dyn Fn([u8; 6]) + Send + Sync>>,
+    extended_advertisements: Vec<Arc<RwLock<ExtendedAdvertisement>>>,
+    periodic_advertisers: Vec<Arc<RwLock<PeriodicAdvertiser>>>,
+    tx_power_levels: Vec<(TxPowerType, TxPowerLevel)>,
+    #[allow(clippy::type_complexity)]
+    connection_parameters_callback: Option<
+        Arc<dyn Fn(Connection, Result<ConnectionParameters, esp_bt_status_t>) + Send + Sync>,
+    >,
+    security_config: SecurityConfig,
+    pairing_callbacks: PairingCallbacks,
+    authentication_callback: Option<Arc<dyn Fn(AuthenticationResult) + Send + Sync>>,
+    authorizer: Option<Authorizer>,
+    encryption_requirement: Option<EncryptionRequirement>,
+    oob_callbacks: OobCallbacks,
+    erase_nvs_on_failure: bool,
 }
 
 unsafe impl Send for GattServer {}
@@ -105,7 +172,7 @@ impl GattServer {
     ///
     /// # Panics
     ///
-    /// Panics if a profile's lock is poisoned.
+    /// Panics if a profile's or an advertising set's lock is poisoned.
     pub fn start(&mut self) {
         if self.started {
             warn!("GATT server already started.");
@@ -113,7 +180,10 @@ impl GattServer {
         }
 
         self.started = true;
-        Self::initialise_ble_stack();
+        self.initialise_ble_stack();
+        self.apply_tx_power_levels();
+
+        self.inherit_authorizers();
 
         // Registration of profiles, services, characteristics and descriptors.
         self.profiles.iter().for_each(|profile| {
@@ -188,6 +258,64 @@ impl GattServer {
         self
     }
 
+    /// Configures the advertisement and scan response data in the stack.
+    ///
+    /// Payloads that include the Tx Power Level are encoded here, so that they report the actual advertising power.
+    /// Advertising starts as soon as the stack acknowledges the new data.
+    pub(crate) fn configure_advertisement_data(&mut self) {
+        unsafe {
+            // Advertisement data.
+            if self.advertisement_data.include_txpower
+                || !self.broadcast_service_data().is_empty()
+            {
+                let mut payload = self.raw_advertisement_payload(&self.advertisement_data);
+                esp_nofail!(esp_ble_gap_config_adv_data_raw(
+                    payload.as_mut_ptr(),
+                    payload.len() as u32
+                ));
+            } else {
+                esp_nofail!(esp_ble_gap_config_adv_data(&mut self.advertisement_data));
+            }
+
+            // Scan response data.
+            if self.scan_response_data.include_txpower {
+                let mut payload = self.raw_advertisement_payload(&self.scan_response_data);
+                esp_nofail!(esp_ble_gap_config_scan_rsp_data_raw(
+                    payload.as_mut_ptr(),
+                    payload.len() as u32
+                ));
+            } else {
+                esp_nofail!(esp_ble_gap_config_adv_data(&mut self.scan_response_data));
+            }
+        }
+    }
+
+    /// Starts advertising with the configured data.
+    ///
+    /// Connectable advertising is not restarted while a client is connected, because it would
+    /// accept other connections: it restarts when the last client disconnects.
+    pub(crate) fn restart_advertising(&mut self) {
+        let connectable = [
+            esp_ble_adv_type_t_ADV_TYPE_IND,
+            esp_ble_adv_type_t_ADV_TYPE_DIRECT_IND_HIGH,
+            esp_ble_adv_type_t_ADV_TYPE_DIRECT_IND_LOW,
+        ]
+        .contains(&self.advertisement_parameters.adv_type);
+
+        if connectable && !self.active_connections.is_empty() {
+            debug!("Not restarting connectable advertising while a client is connected.");
+            return;
+        }
+
+        info!("Starting BLE GAP advertisement.");
+
+        unsafe {
+            esp_nofail!(esp_ble_gap_start_advertising(
+                &mut self.advertisement_parameters
+            ));
+        }
+    }
+
     pub(crate) fn get_profile(&self, interface: u8) -> Option<Arc<RwLock<Profile>>> {
         self.profiles
             .iter()
@@ -196,17 +324,27 @@ impl GattServer {
     }
 
     #[allow(clippy::too_many_lines)]
-    fn initialise_ble_stack() {
+    fn initialise_ble_stack(&self) {
         info!("Initialising BLE stack.");
 
         // NVS initialisation.
-        unsafe {
-            let result = nvs_flash_init();
-            if result == ESP_ERR_NVS_NO_FREE_PAGES || result == ESP_ERR_NVS_NEW_VERSION_FOUND {
-                warn!("NVS initialisation failed. Erasing NVS.");
+        let mut result = unsafe { nvs_flash_init() };
+        if (result == ESP_ERR_NVS_NO_FREE_PAGES || result == ESP_ERR_NVS_NEW_VERSION_FOUND)
+            && self.erase_nvs_on_failure
+        {
+            warn!("NVS initialisation failed. Erasing NVS.");
+            unsafe {
                 esp_nofail!(nvs_flash_erase());
-                esp_nofail!(nvs_flash_init());
             }
+            result = unsafe { nvs_flash_init() };
+        }
+
+        if let Err(error) = esp!(result) {
+            warn!(
+                "NVS initialisation failed ({}). Values will not be persisted.",
+                error
+            );
+            Self::use_memory_storage();
         }
 
         #[cfg(esp32)]
@@ -360,6 +498,9 @@ impl GattServer {
                 Self::default_gap_callback
             )));
         }
+
+        // Security Manager configuration.
+        self.configure_security();
     }
 
     /// Calls the global server's GATT event callback.
@@ -0,0 +1,118 @@
+use std::sync::{Arc, RwLock};
+
+use esp_idf_sys::*;
+use log::{debug, warn};
+
+use crate::{
+    gatt_server::{Characteristic, GattServer},
+    utilities::{BleUuid, Connection},
+};
+
+impl GattServer {
+    /// Notifies a characteristic to each subscribed client, with the values computed for its connection.
+    ///
+    /// Unlike [`Characteristic::set_value`], which notifies the latest value when the stack
+    /// confirms the update, every value is sent, in order.
+    ///
+    /// If a value cannot be sent to a client, the following ones are dropped for that client,
+    /// the other clients are still notified, and the first error is returned.
+    pub(crate) fn notify_each(
+        &self,
+        characteristic: &Arc<RwLock<Characteristic>>,
+        values: impl Fn(&Connection) -> Vec<Vec<u8>>,
+    ) -> Result<(), EspError> {
+        let not_registered = || EspError::from(ESP_ERR_INVALID_STATE as esp_err_t).unwrap();
+
+        let Some(interface) = self.interface_of(characteristic) else {
+            warn!("Cannot notify a characteristic that is not registered.");
+            return Err(not_registered());
+        };
+
+        let characteristic = characteristic.read().unwrap();
+
+        let (Some(handle), Some(cccd_handle)) = (
+            characteristic.attribute_handle,
+            characteristic
+                .descriptors
+                .iter()
+                .find(|descriptor| descriptor.read().unwrap().uuid == BleUuid::Uuid16(0x2902))
+                .and_then(|descriptor| descriptor.read().unwrap().attribute_handle),
+        ) else {
+            warn!(
+                "Cannot notify {}, which is not registered or has no CCCD.",
+                characteristic
+            );
+            return Err(not_registered());
+        };
+
+        let mut result = Ok(());
+
+        for connection in &self.active_connections {
+            // Get the current status of the CCCD via a fake read operation.
+            let simulated_read_param = esp_ble_gatts_cb_param_t_gatts_read_evt_param {
+                bda: connection.remote_bda,
+                conn_id: connection.id,
+                handle: cccd_handle,
+                ..Default::default()
+            };
+
+            if !characteristic
+                .get_cccd_status(simulated_read_param)
+                .is_some_and(|(notification, _)| notification)
+            {
+                continue;
+            }
+
+            for mut value in values(connection) {
+                debug!(
+                    "Notifying {} value {:02X?} to {}.",
+                    characteristic, value, connection
+                );
+
+                #[allow(clippy::cast_possible_truncation)]
+                let sent = unsafe {
+                    esp!(esp_ble_gatts_send_indicate(
+                        interface,
+                        connection.id,
+                        handle,
+                        value.len() as u16,
+                        value.as_mut_ptr(),
+                        false
+                    ))
+                };
+
+                if let Err(error) = sent {
+                    warn!(
+                        "Failed to notify {} to {}, dropping the remaining values: {}.",
+                        characteristic, connection, error
+                    );
+                    result = result.and(Err(error));
+                    break;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns the interface of the profile that contains a characteristic.
+    fn interface_of(&self, characteristic: &Arc<RwLock<Characteristic>>) -> Option<u8> {
+        self.profiles.iter().find_map(|profile| {
+            let profile = profile.read().unwrap();
+
+            profile
+                .services
+                .iter()
+                .any(|service| {
+                    service
+                        .read()
+                        .unwrap()
+                        .characteristics
+                        .iter()
+                        .any(|existing| Arc::ptr_eq(existing, characteristic))
+                })
+                .then_some(profile.interface)
+                .flatten()
+        })
+    }
+}
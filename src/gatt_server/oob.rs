@@ -0,0 +1,336 @@
+use std::sync::Arc;
+
+use esp_idf_sys::*;
+use log::{debug, warn};
+
+use crate::gatt_server::GattServer;
+
+/// Represents the LE Secure Connections out-of-band data of a device.
+///
+/// The local data is generated with [`GattServer::generate_oob_data`] and shared with the client,
+/// for example through NFC or a QR code. The client's data is passed to [`ScOobRequest::reply`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OobData {
+    confirm: [u8; 16],
+    random: [u8; 16],
+}
+
+impl OobData {
+    /// Creates a new [`OobData`] from the confirm and random values.
+    #[must_use]
+    pub const fn new(confirm: [u8; 16], random: [u8; 16]) -> Self {
+        Self { confirm, random }
+    }
+
+    /// Returns the confirm value.
+    #[must_use]
+    pub const fn confirm(&self) -> [u8; 16] {
+        self.confirm
+    }
+
+    /// Returns the random value.
+    #[must_use]
+    pub const fn random(&self) -> [u8; 16] {
+        self.random
+    }
+}
+
+/// Represents a request from the stack for the temporary key exchanged out of band, with legacy pairing.
+///
+/// The request should be answered with [`OobRequest::reply`] or [`OobRequest::reject`].
+/// A request dropped without an answer is rejected.
+#[derive(Debug)]
+pub struct OobRequest {
+    address: [u8; 6],
+    answered: bool,
+}
+
+impl OobRequest {
+    /// Returns the address of the client that is pairing.
+    #[must_use]
+    pub const fn address(&self) -> [u8; 6] {
+        self.address
+    }
+
+    /// Answers with the temporary key exchanged out of band.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn reply(mut self, mut temporary_key: [u8; 16]) -> Result<(), EspError> {
+        self.answered = true;
+
+        unsafe {
+            esp!(esp_ble_oob_req_reply(
+                self.address.as_mut_ptr(),
+                temporary_key.as_mut_ptr(),
+                temporary_key.len() as u8
+            ))
+        }
+    }
+
+    /// Rejects the pairing, because no out-of-band data is available.
+    ///
+    /// The stack does not accept an empty temporary key, so the client is disconnected instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot disconnect the client.
+    pub fn reject(mut self) -> Result<(), EspError> {
+        self.answered = true;
+        reject_pairing(self.address)
+    }
+}
+
+impl Drop for OobRequest {
+    fn drop(&mut self) {
+        if !self.answered {
+            warn!(
+                "Out-of-band request of {:02X?} dropped without an answer. Rejecting pairing.",
+                self.address
+            );
+
+            if let Err(error) = reject_pairing(self.address) {
+                warn!("Cannot reject out-of-band request: {}.", error);
+            }
+        }
+    }
+}
+
+/// Represents a request from the stack for the out-of-band data of the client, with LE Secure Connections.
+///
+/// The request should be answered with [`ScOobRequest::reply`] or [`ScOobRequest::reject`].
+/// A request dropped without an answer is rejected.
+#[derive(Debug)]
+pub struct ScOobRequest {
+    address: [u8; 6],
+    answered: bool,
+}
+
+impl ScOobRequest {
+    /// Returns the address of the client that is pairing.
+    #[must_use]
+    pub const fn address(&self) -> [u8; 6] {
+        self.address
+    }
+
+    /// Answers with the out-of-band data received from the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    #[cfg(esp_idf_version_major = "5")]
+    pub fn reply(mut self, data: OobData) -> Result<(), EspError> {
+        self.answered = true;
+
+        let mut confirm = data.confirm;
+        let mut random = data.random;
+
+        unsafe {
+            esp!(esp_ble_sc_oob_req_reply(
+                self.address.as_mut_ptr(),
+                confirm.as_mut_ptr(),
+                random.as_mut_ptr()
+            ))
+        }
+    }
+
+    /// Answers with the out-of-band data received from the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ESP_ERR_NOT_SUPPORTED` error, because this ESP-IDF version
+    /// does not support out-of-band data with LE Secure Connections.
+    #[cfg(not(esp_idf_version_major = "5"))]
+    pub fn reply(self, _data: OobData) -> Result<(), EspError> {
+        Err(sc_oob_not_supported())
+    }
+
+    /// Rejects the pairing, because no out-of-band data is available.
+    ///
+    /// The client is disconnected, so that the pairing does not wait for the SMP timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot disconnect the client.
+    pub fn reject(mut self) -> Result<(), EspError> {
+        self.answered = true;
+        reject_pairing(self.address)
+    }
+}
+
+impl Drop for ScOobRequest {
+    fn drop(&mut self) {
+        if !self.answered {
+            warn!(
+                "Secure Connections out-of-band request of {:02X?} dropped without an answer. Rejecting pairing.",
+                self.address
+            );
+
+            if let Err(error) = reject_pairing(self.address) {
+                warn!(
+                    "Cannot reject Secure Connections out-of-band request: {}.",
+                    error
+                );
+            }
+        }
+    }
+}
+
+/// Aborts an out-of-band pairing by disconnecting the client.
+fn reject_pairing(mut address: [u8; 6]) -> Result<(), EspError> {
+    unsafe { esp!(esp_ble_gap_disconnect(address.as_mut_ptr())) }
+}
+
+/// The callbacks used to exchange out-of-band data during pairing.
+#[derive(Default, Clone)]
+#[cfg_attr(not(esp_idf_version_major = "5"), allow(dead_code))]
+pub(crate) struct OobCallbacks {
+    oob_request: Option<Arc<dyn Fn(OobRequest) + Send + Sync>>,
+    sc_oob_request: Option<Arc<dyn Fn(ScOobRequest) + Send + Sync>>,
+    local_oob_data: Option<Arc<dyn Fn(OobData) + Send + Sync>>,
+}
+
+impl GattServer {
+    /// Sets a callback that is called when the temporary key exchanged out of band is needed for legacy pairing.
+    ///
+    /// Out-of-band pairing must be enabled in the [`SecurityConfig`].
+    /// Without a callback, out-of-band requests are rejected.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    ///
+    /// [`SecurityConfig`]: crate::utilities::SecurityConfig
+    pub fn on_oob_request(
+        &mut self,
+        callback: impl Fn(OobRequest) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.oob_callbacks.oob_request = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when the client's out-of-band data is needed for LE Secure Connections pairing.
+    ///
+    /// Out-of-band pairing must be enabled in the [`SecurityConfig`].
+    /// Without a callback, out-of-band requests are rejected.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    ///
+    /// [`SecurityConfig`]: crate::utilities::SecurityConfig
+    pub fn on_sc_oob_request(
+        &mut self,
+        callback: impl Fn(ScOobRequest) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.oob_callbacks.sc_oob_request = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback that receives the local out-of-band data, generated with [`GattServer::generate_oob_data`].
+    ///
+    /// The data must be shared with the client, for example through NFC or a QR code.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_local_oob_data(
+        &mut self,
+        callback: impl Fn(OobData) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.oob_callbacks.local_oob_data = Some(Arc::new(callback));
+        self
+    }
+
+    /// Generates new local out-of-band data for LE Secure Connections pairing.
+    ///
+    /// The data is passed to the callback set with [`GattServer::on_local_oob_data`].
+    /// The server must be started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot generate the data.
+    #[cfg(esp_idf_version_major = "5")]
+    #[allow(clippy::unused_self)]
+    pub fn generate_oob_data(&self) -> Result<(), EspError> {
+        unsafe { esp!(esp_ble_create_sc_oob_data()) }
+    }
+
+    /// Generates new local out-of-band data for LE Secure Connections pairing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ESP_ERR_NOT_SUPPORTED` error, because this ESP-IDF version
+    /// does not support out-of-band data with LE Secure Connections.
+    #[cfg(not(esp_idf_version_major = "5"))]
+    #[allow(clippy::unused_self)]
+    pub fn generate_oob_data(&self) -> Result<(), EspError> {
+        Err(sc_oob_not_supported())
+    }
+
+    /// Handles a legacy out-of-band data request.
+    pub(crate) fn on_oob_request_event(&self, param: esp_ble_sec_req_t) {
+        debug!("Out-of-band data requested by {:02X?}.", param.bd_addr);
+
+        let request = OobRequest {
+            address: param.bd_addr,
+            answered: false,
+        };
+
+        if let Some(callback) = &self.oob_callbacks.oob_request {
+            callback(request);
+        } else {
+            warn!("No out-of-band request callback set. Rejecting pairing.");
+            if let Err(error) = request.reject() {
+                warn!("Cannot reject out-of-band request: {}.", error);
+            }
+        }
+    }
+
+    /// Handles an LE Secure Connections out-of-band data request.
+    #[cfg(esp_idf_version_major = "5")]
+    pub(crate) fn on_sc_oob_request_event(&self, param: esp_ble_sec_req_t) {
+        debug!(
+            "Secure Connections out-of-band data requested by {:02X?}.",
+            param.bd_addr
+        );
+
+        let request = ScOobRequest {
+            address: param.bd_addr,
+            answered: false,
+        };
+
+        if let Some(callback) = &self.oob_callbacks.sc_oob_request {
+            callback(request);
+        } else {
+            warn!("No Secure Connections out-of-band request callback set. Rejecting pairing.");
+            if let Err(error) = request.reject() {
+                warn!(
+                    "Cannot reject Secure Connections out-of-band request: {}.",
+                    error
+                );
+            }
+        }
+    }
+
+    /// Handles the generation of local out-of-band data.
+    #[cfg(esp_idf_version_major = "5")]
+    pub(crate) fn on_local_oob_data_event(&self, data: OobData) {
+        debug!("Local out-of-band data generated.");
+
+        if let Some(callback) = &self.oob_callbacks.local_oob_data {
+            callback(data);
+        } else {
+            warn!("No local out-of-band data callback set. Ignoring generated data.");
+        }
+    }
+}
+
+/// Returns the error used when LE Secure Connections out-of-band data is not available.
+#[cfg(not(esp_idf_version_major = "5"))]
+fn sc_oob_not_supported() -> EspError {
+    warn!("Out-of-band data with LE Secure Connections requires ESP-IDF 5.");
+    EspError::from(ESP_ERR_NOT_SUPPORTED as esp_err_t).unwrap()
+}
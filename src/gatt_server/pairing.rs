@@ -0,0 +1,340 @@
+use std::sync::Arc;
+
+use esp_idf_sys::*;
+use log::{debug, info, warn};
+
+use crate::gatt_server::GattServer;
+
+/// Represents a request from the stack to enter the passkey displayed by the client.
+///
+/// The request should be answered with [`PasskeyRequest::reply`] or [`PasskeyRequest::reject`].
+/// A request dropped without an answer is rejected.
+#[derive(Debug)]
+pub struct PasskeyRequest {
+    address: [u8; 6],
+    answered: bool,
+}
+
+impl PasskeyRequest {
+    /// Returns the address of the client that is pairing.
+    #[must_use]
+    pub const fn address(&self) -> [u8; 6] {
+        self.address
+    }
+
+    /// Answers with the passkey entered by the user, between 0 and 999999.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    pub fn reply(mut self, passkey: u32) -> Result<(), EspError> {
+        self.answered = true;
+
+        unsafe {
+            esp!(esp_ble_passkey_reply(
+                self.address.as_mut_ptr(),
+                true,
+                passkey
+            ))
+        }
+    }
+
+    /// Rejects the pairing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    pub fn reject(mut self) -> Result<(), EspError> {
+        self.answered = true;
+        unsafe { esp!(esp_ble_passkey_reply(self.address.as_mut_ptr(), false, 0)) }
+    }
+}
+
+impl Drop for PasskeyRequest {
+    fn drop(&mut self) {
+        if !self.answered {
+            warn!(
+                "Passkey request of {:02X?} dropped without an answer. Rejecting pairing.",
+                self.address
+            );
+
+            if let Err(error) =
+                unsafe { esp!(esp_ble_passkey_reply(self.address.as_mut_ptr(), false, 0)) }
+            {
+                warn!("Cannot reject passkey request: {}.", error);
+            }
+        }
+    }
+}
+
+/// Represents a numeric comparison request, where the user checks that both devices show the same value.
+///
+/// The request should be answered with [`NumericComparison::accept`] or [`NumericComparison::reject`].
+/// A request dropped without an answer is rejected.
+#[derive(Debug)]
+pub struct NumericComparison {
+    address: [u8; 6],
+    value: u32,
+    answered: bool,
+}
+
+impl NumericComparison {
+    /// Returns the address of the client that is pairing.
+    #[must_use]
+    pub const fn address(&self) -> [u8; 6] {
+        self.address
+    }
+
+    /// Returns the six-digit value to show to the user.
+    #[must_use]
+    pub const fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Confirms that the values match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    pub fn accept(mut self) -> Result<(), EspError> {
+        self.answered = true;
+        unsafe { esp!(esp_ble_confirm_reply(self.address.as_mut_ptr(), true)) }
+    }
+
+    /// Rejects the pairing, because the values do not match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    pub fn reject(mut self) -> Result<(), EspError> {
+        self.answered = true;
+        unsafe { esp!(esp_ble_confirm_reply(self.address.as_mut_ptr(), false)) }
+    }
+}
+
+impl Drop for NumericComparison {
+    fn drop(&mut self) {
+        if !self.answered {
+            warn!(
+                "Numeric comparison of {:02X?} dropped without an answer. Rejecting pairing.",
+                self.address
+            );
+
+            if let Err(error) =
+                unsafe { esp!(esp_ble_confirm_reply(self.address.as_mut_ptr(), false)) }
+            {
+                warn!("Cannot reject numeric comparison: {}.", error);
+            }
+        }
+    }
+}
+
+/// Represents a security request sent by a client.
+///
+/// The request should be answered with [`SecurityRequest::accept`] or [`SecurityRequest::reject`].
+/// A request dropped without an answer is rejected.
+#[derive(Debug)]
+pub struct SecurityRequest {
+    address: [u8; 6],
+    answered: bool,
+}
+
+impl SecurityRequest {
+    /// Returns the address of the client that sent the request.
+    #[must_use]
+    pub const fn address(&self) -> [u8; 6] {
+        self.address
+    }
+
+    /// Accepts the request, starting pairing or encryption.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    pub fn accept(mut self) -> Result<(), EspError> {
+        self.answered = true;
+        unsafe { esp!(esp_ble_gap_security_rsp(self.address.as_mut_ptr(), true)) }
+    }
+
+    /// Rejects the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the reply.
+    pub fn reject(mut self) -> Result<(), EspError> {
+        self.answered = true;
+        unsafe { esp!(esp_ble_gap_security_rsp(self.address.as_mut_ptr(), false)) }
+    }
+}
+
+impl Drop for SecurityRequest {
+    fn drop(&mut self) {
+        if !self.answered {
+            warn!(
+                "Security request of {:02X?} dropped without an answer. Rejecting it.",
+                self.address
+            );
+
+            if let Err(error) =
+                unsafe { esp!(esp_ble_gap_security_rsp(self.address.as_mut_ptr(), false)) }
+            {
+                warn!("Cannot reject security request: {}.", error);
+            }
+        }
+    }
+}
+
+/// The callbacks used to interact with the user during pairing.
+#[derive(Default, Clone)]
+pub(crate) struct PairingCallbacks {
+    passkey_display: Option<Arc<dyn Fn([u8; 6], u32) + Send + Sync>>,
+    passkey_request: Option<Arc<dyn Fn(PasskeyRequest) + Send + Sync>>,
+    numeric_comparison: Option<Arc<dyn Fn(NumericComparison) + Send + Sync>>,
+    security_request: Option<Arc<dyn Fn(SecurityRequest) + Send + Sync>>,
+}
+
+impl GattServer {
+    /// Sets a callback that is called when a passkey must be shown to the user.
+    ///
+    /// The callback receives the address of the client and the six-digit passkey,
+    /// which the user enters on the client.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_passkey_display(
+        &mut self,
+        callback: impl Fn([u8; 6], u32) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.pairing_callbacks.passkey_display = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when the user must enter the passkey shown by the client.
+    ///
+    /// The [`PasskeyRequest`] can be answered later, for example from another thread.
+    /// Without a callback, passkey requests are rejected.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_passkey_request(
+        &mut self,
+        callback: impl Fn(PasskeyRequest) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.pairing_callbacks.passkey_request = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when the user must confirm that both devices show the same value.
+    ///
+    /// The [`NumericComparison`] can be answered later, for example when the user presses a button.
+    /// Without a callback, numeric comparisons are accepted, unless the [`SecurityConfig`]
+    /// requires MITM protection, in which case they are rejected.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    ///
+    /// [`SecurityConfig`]: crate::utilities::SecurityConfig
+    pub fn on_numeric_comparison(
+        &mut self,
+        callback: impl Fn(NumericComparison) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.pairing_callbacks.numeric_comparison = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when a client asks to pair or to encrypt the link.
+    ///
+    /// Without a callback, security requests are accepted, unless the [`SecurityConfig`]
+    /// requires MITM protection, in which case they are rejected.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    ///
+    /// [`SecurityConfig`]: crate::utilities::SecurityConfig
+    pub fn on_security_request(
+        &mut self,
+        callback: impl Fn(SecurityRequest) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.pairing_callbacks.security_request = Some(Arc::new(callback));
+        self
+    }
+
+    /// Handles a passkey notification.
+    pub(crate) fn on_passkey_notification(&self, param: esp_ble_sec_key_notif_t) {
+        info!("Passkey for {:02X?}: {:06}.", param.bd_addr, param.passkey);
+
+        if let Some(callback) = &self.pairing_callbacks.passkey_display {
+            callback(param.bd_addr, param.passkey);
+        }
+    }
+
+    /// Handles a passkey request.
+    pub(crate) fn on_passkey_request_event(&self, param: esp_ble_sec_req_t) {
+        debug!("Passkey requested by {:02X?}.", param.bd_addr);
+
+        let request = PasskeyRequest {
+            address: param.bd_addr,
+            answered: false,
+        };
+
+        if let Some(callback) = &self.pairing_callbacks.passkey_request {
+            callback(request);
+        } else {
+            warn!("No passkey request callback set. Rejecting pairing.");
+            if let Err(error) = request.reject() {
+                warn!("Cannot reject passkey request: {}.", error);
+            }
+        }
+    }
+
+    /// Handles a numeric comparison request.
+    pub(crate) fn on_numeric_comparison_event(&self, param: esp_ble_sec_key_notif_t) {
+        debug!(
+            "Numeric comparison requested by {:02X?}: {:06}.",
+            param.bd_addr, param.passkey
+        );
+
+        let comparison = NumericComparison {
+            address: param.bd_addr,
+            value: param.passkey,
+            answered: false,
+        };
+
+        if let Some(callback) = &self.pairing_callbacks.numeric_comparison {
+            callback(comparison);
+        } else if self.security_config.mitm {
+            // Accepting without a user confirmation would downgrade to Just Works.
+            warn!("No numeric comparison callback set, but MITM protection is required. Rejecting pairing.");
+            if let Err(error) = comparison.reject() {
+                warn!("Cannot reject numeric comparison: {}.", error);
+            }
+        } else if let Err(error) = comparison.accept() {
+            warn!("Cannot accept numeric comparison: {}.", error);
+        }
+    }
+
+    /// Handles a security request.
+    pub(crate) fn on_security_request_event(&self, param: esp_ble_sec_req_t) {
+        debug!("Security requested by {:02X?}.", param.bd_addr);
+
+        let request = SecurityRequest {
+            address: param.bd_addr,
+            answered: false,
+        };
+
+        if let Some(callback) = &self.pairing_callbacks.security_request {
+            callback(request);
+        } else if self.security_config.mitm {
+            warn!("No security request callback set, but MITM protection is required. Rejecting request.");
+            if let Err(error) = request.reject() {
+                warn!("Cannot reject security request: {}.", error);
+            }
+        } else if let Err(error) = request.accept() {
+            warn!("Cannot accept security request: {}.", error);
+        }
+    }
+}
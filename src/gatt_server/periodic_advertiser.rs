@@ -0,0 +1,391 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use esp_idf_sys::*;
+use log::{debug, info, warn};
+
+#[cfg(not(esp_idf_bt_ble_50_features_supported))]
+use crate::gatt_server::extended_advertisement::not_supported;
+use crate::gatt_server::{ExtendedAdvertisement, GattServer};
+
+/// The maximum length of a periodic advertisement payload.
+const MAX_PERIODIC_DATA_LENGTH: usize = 1650;
+
+/// Represents the status of a [`PeriodicAdvertiser`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodicAdvertisingStatus {
+    /// The advertiser is not running.
+    Idle,
+    /// The advertiser is being configured, and will start as soon as the stack is ready.
+    Starting,
+    /// The advertiser is broadcasting its payload.
+    Active,
+    /// The stack rejected the configuration or the start request.
+    Failed,
+}
+
+/// Represents a BLE 5 periodic advertiser, used to broadcast data to many receivers without connections.
+///
+/// A periodic advertiser is tied to an [`ExtendedAdvertisement`] set, which must be non-connectable,
+/// non-scannable and non-anonymous. Receivers find the periodic train by synchronising to the set,
+/// so the set must be started too.
+///
+/// # Notes
+///
+/// Periodic advertising is only available on chips that support BLE 5 (ESP32-C3, ESP32-S3),
+/// with `CONFIG_BT_BLE_50_FEATURES_SUPPORTED` enabled.
+/// On the original ESP32, [`PeriodicAdvertiser::start`] and [`PeriodicAdvertiser::stop`] return
+/// an `ESP_ERR_NOT_SUPPORTED` error.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(esp_idf_bt_ble_50_features_supported), allow(dead_code))]
+pub struct PeriodicAdvertiser {
+    advertisement: Arc<RwLock<ExtendedAdvertisement>>,
+    pub(crate) instance: u8,
+    interval: (Duration, Duration),
+    include_tx_power: bool,
+    data: Vec<u8>,
+    pub(crate) status: PeriodicAdvertisingStatus,
+    /// Whether the data has been sent to the stack at least once.
+    pub(crate) configured: bool,
+}
+
+impl PeriodicAdvertiser {
+    /// Creates a new [`PeriodicAdvertiser`] tied to the given [`ExtendedAdvertisement`] set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the advertising set's lock is poisoned.
+    #[must_use]
+    pub fn new(advertisement: &Arc<RwLock<ExtendedAdvertisement>>) -> Self {
+        let instance = advertisement.read().unwrap().instance;
+
+        Self {
+            advertisement: advertisement.clone(),
+            instance,
+            interval: (Duration::from_millis(100), Duration::from_millis(200)),
+            include_tx_power: false,
+            data: Vec::new(),
+            status: PeriodicAdvertisingStatus::Idle,
+            configured: false,
+        }
+    }
+
+    /// Sets the minimum and maximum periodic advertising interval.
+    ///
+    /// The interval is rounded to a multiple of 1.25 ms, and must be between 7.5 ms and 81.91875 s.
+    pub fn interval(&mut self, min: Duration, max: Duration) -> &mut Self {
+        if min > max {
+            warn!("The minimum periodic advertising interval is greater than the maximum. Ignoring interval.");
+            return self;
+        }
+
+        if min < Duration::from_micros(7500) || max > Duration::from_micros(81_918_750) {
+            warn!("The periodic advertising interval must be between 7.5 ms and 81.91875 s. Ignoring interval.");
+            return self;
+        }
+
+        self.interval = (min, max);
+        self
+    }
+
+    /// Includes the transmit power in the periodic advertising PDUs.
+    pub fn include_tx_power(&mut self) -> &mut Self {
+        self.include_tx_power = true;
+        self
+    }
+
+    /// Sets the periodic advertisement payload.
+    ///
+    /// The payload can be up to 1650 bytes long. Payloads that do not fit in a single HCI command
+    /// are split in fragments by the stack.
+    /// See [`AdvertisementData`] for an easy way of building a payload.
+    ///
+    /// If the advertiser is already running, the payload is updated on the fly,
+    /// and receivers get it from the next periodic event.
+    ///
+    /// [`AdvertisementData`]: crate::utilities::AdvertisementData
+    pub fn data<T: Into<Vec<u8>>>(&mut self, data: T) -> &mut Self {
+        let data = data.into();
+
+        if data.len() > MAX_PERIODIC_DATA_LENGTH {
+            warn!(
+                "Periodic advertisement payload is too long. The maximum length is {} bytes. Ignoring payload.",
+                MAX_PERIODIC_DATA_LENGTH
+            );
+            return self;
+        }
+
+        self.data = data;
+
+        if self.configured {
+            self.configure_data();
+        }
+
+        self
+    }
+
+    /// Returns the current status of the [`PeriodicAdvertiser`].
+    #[must_use]
+    pub const fn status(&self) -> PeriodicAdvertisingStatus {
+        self.status
+    }
+
+    /// Returns a reference to the built [`PeriodicAdvertiser`] behind an `Arc` and an `RwLock`.
+    ///
+    /// The returned value can be passed to any function of this crate that expects a [`PeriodicAdvertiser`].
+    /// It can be used in different threads, because it is protected by an `RwLock`.
+    #[must_use]
+    pub fn build(&self) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(self.clone()))
+    }
+
+    /// Starts periodic advertising.
+    ///
+    /// The advertiser must have been added to the server with [`GattServer::periodic_advertiser`],
+    /// and the server must be started. The advertising set must have been started, and the stack
+    /// must have acknowledged its parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ESP_ERR_INVALID_STATE` error if the advertiser is already running,
+    /// if the parameters of the advertising set are not configured yet,
+    /// or if the advertising set cannot carry periodic advertisements.
+    /// Returns an error if the Bluetooth stack rejects the parameters,
+    /// or if periodic advertising is not supported on this chip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the advertising set's lock is poisoned.
+    pub fn start(&mut self) -> Result<(), EspError> {
+        if matches!(
+            self.status,
+            PeriodicAdvertisingStatus::Starting | PeriodicAdvertisingStatus::Active
+        ) {
+            warn!(
+                "Periodic advertiser on instance {} is already running.",
+                self.instance
+            );
+            return Err(EspError::from(ESP_ERR_INVALID_STATE as esp_err_t).unwrap());
+        }
+
+        if !self
+            .advertisement
+            .read()
+            .unwrap()
+            .supports_periodic_advertising()
+        {
+            warn!(
+                "{} must be non-connectable, non-scannable, non-anonymous and non-legacy to carry periodic advertisements.",
+                self.advertisement.read().unwrap()
+            );
+            return Err(EspError::from(ESP_ERR_INVALID_STATE as esp_err_t).unwrap());
+        }
+
+        // The periodic parameters are tied to the advertising set, which the stack creates with its parameters.
+        #[cfg(esp_idf_bt_ble_50_features_supported)]
+        if !self.advertisement.read().unwrap().configured {
+            warn!(
+                "{} must be started before its periodic advertiser.",
+                self.advertisement.read().unwrap()
+            );
+            return Err(EspError::from(ESP_ERR_INVALID_STATE as esp_err_t).unwrap());
+        }
+
+        info!(
+            "Starting periodic advertiser on instance {}.",
+            self.instance
+        );
+
+        self.configure_parameters()?;
+        self.status = PeriodicAdvertisingStatus::Starting;
+
+        Ok(())
+    }
+
+    /// Stops periodic advertising.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot stop the advertiser,
+    /// or if periodic advertising is not supported on this chip.
+    pub fn stop(&mut self) -> Result<(), EspError> {
+        self.stop_advertiser()
+    }
+
+    #[cfg(esp_idf_bt_ble_50_features_supported)]
+    #[allow(clippy::cast_possible_truncation)]
+    fn configure_parameters(&self) -> Result<(), EspError> {
+        // The interval is expressed in units of 1.25 ms.
+        let to_units = |interval: Duration| (interval.as_micros() / 1250) as u16;
+
+        let parameters = esp_ble_gap_periodic_adv_params_t {
+            interval_min: to_units(self.interval.0),
+            interval_max: to_units(self.interval.1),
+            // Bit 6: include the transmit power.
+            properties: if self.include_tx_power { 1 << 6 } else { 0 },
+        };
+
+        unsafe {
+            esp!(esp_ble_gap_periodic_adv_set_params(
+                self.instance,
+                &parameters
+            ))
+        }
+    }
+
+    #[cfg(not(esp_idf_bt_ble_50_features_supported))]
+    #[allow(clippy::unused_self)]
+    fn configure_parameters(&self) -> Result<(), EspError> {
+        Err(not_supported())
+    }
+
+    #[cfg(esp_idf_bt_ble_50_features_supported)]
+    fn stop_advertiser(&self) -> Result<(), EspError> {
+        unsafe { esp!(esp_ble_gap_periodic_adv_stop(self.instance)) }
+    }
+
+    #[cfg(not(esp_idf_bt_ble_50_features_supported))]
+    #[allow(clippy::unused_self)]
+    fn stop_advertiser(&self) -> Result<(), EspError> {
+        Err(not_supported())
+    }
+
+    /// Sends the periodic advertisement payload to the stack.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn configure_data(&self) {
+        debug!(
+            "Configuring periodic advertisement payload on instance {}.",
+            self.instance
+        );
+
+        #[cfg(esp_idf_bt_ble_50_features_supported)]
+        unsafe {
+            esp_nofail!(esp_ble_gap_config_periodic_adv_data_raw(
+                self.instance,
+                self.data.len() as u16,
+                self.data.as_ptr()
+            ));
+        }
+    }
+}
+
+impl GattServer {
+    /// Adds a [`PeriodicAdvertiser`] to the server.
+    ///
+    /// The advertiser does not start until [`PeriodicAdvertiser::start`] is called.
+    pub fn periodic_advertiser(
+        &mut self,
+        advertiser: &Arc<RwLock<PeriodicAdvertiser>>,
+    ) -> &mut Self {
+        let instance = advertiser.read().unwrap().instance;
+        if self.get_periodic_advertiser(instance).is_some() {
+            warn!(
+                "A periodic advertiser on instance {} already exists. Ignoring periodic advertiser.",
+                instance
+            );
+            return self;
+        }
+
+        self.periodic_advertisers.push(advertiser.clone());
+        self
+    }
+
+    pub(crate) fn get_periodic_advertiser(
+        &self,
+        instance: u8,
+    ) -> Option<Arc<RwLock<PeriodicAdvertiser>>> {
+        self.periodic_advertisers
+            .iter()
+            .find(|advertiser| advertiser.read().unwrap().instance == instance)
+            .cloned()
+    }
+}
+
+#[cfg(esp_idf_bt_ble_50_features_supported)]
+impl GattServer {
+    /// Handles the completion of the parameters configuration of a periodic advertiser.
+    pub(crate) fn on_periodic_adv_set_params(&mut self, status: esp_bt_status_t, instance: u8) {
+        let Some(advertiser) = self.get_periodic_advertiser(instance) else {
+            warn!("Cannot find periodic advertiser on instance {}.", instance);
+            return;
+        };
+
+        let mut advertiser = advertiser.write().unwrap();
+        if status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "Cannot set parameters of periodic advertiser on instance {}.",
+                instance
+            );
+            advertiser.status = PeriodicAdvertisingStatus::Failed;
+            return;
+        }
+
+        advertiser.configured = true;
+        advertiser.configure_data();
+    }
+
+    /// Handles the completion of the payload configuration of a periodic advertiser.
+    pub(crate) fn on_periodic_adv_data_set(&mut self, status: esp_bt_status_t, instance: u8) {
+        let Some(advertiser) = self.get_periodic_advertiser(instance) else {
+            warn!("Cannot find periodic advertiser on instance {}.", instance);
+            return;
+        };
+
+        let mut advertiser = advertiser.write().unwrap();
+        if status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "Cannot set payload of periodic advertiser on instance {}.",
+                instance
+            );
+            if advertiser.status == PeriodicAdvertisingStatus::Starting {
+                advertiser.status = PeriodicAdvertisingStatus::Failed;
+            }
+            return;
+        }
+
+        if advertiser.status == PeriodicAdvertisingStatus::Starting {
+            unsafe {
+                esp_nofail!(esp_ble_gap_periodic_adv_start(instance));
+            }
+        }
+    }
+
+    /// Handles the start or stop of a periodic advertiser.
+    pub(crate) fn on_periodic_adv_start_stop(
+        &mut self,
+        status: esp_bt_status_t,
+        instance: u8,
+        started: bool,
+    ) {
+        let Some(advertiser) = self.get_periodic_advertiser(instance) else {
+            warn!("Cannot find periodic advertiser on instance {}.", instance);
+            return;
+        };
+
+        let mut advertiser = advertiser.write().unwrap();
+        if status != esp_bt_status_t_ESP_BT_STATUS_SUCCESS {
+            warn!(
+                "Cannot {} periodic advertiser on instance {}.",
+                if started { "start" } else { "stop" },
+                instance
+            );
+            if started {
+                advertiser.status = PeriodicAdvertisingStatus::Failed;
+            }
+            return;
+        }
+
+        debug!(
+            "Periodic advertiser on instance {} {}.",
+            instance,
+            if started { "started" } else { "stopped" }
+        );
+        advertiser.status = if started {
+            PeriodicAdvertisingStatus::Active
+        } else {
+            PeriodicAdvertisingStatus::Idle
+        };
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use log::{debug, warn};
+
+use crate::{
+    gatt_server::{Characteristic, GattServer},
+    utilities::{is_valid_key, AttributeControl, Debouncer, MAX_KEY_LENGTH},
+};
+
+/// Describes where and how the value of a [`Characteristic`] is persisted.
+#[derive(Debug, Clone)]
+pub(crate) struct Persistence {
+    key: String,
+    /// Delays the writes while a client keeps changing the value.
+    debouncer: Option<Debouncer<Vec<u8>>>,
+}
+
+impl Persistence {
+    /// Stores a value written by a client, possibly after the debounce delay.
+    pub(crate) fn store(&self, value: Vec<u8>) {
+        match &self.debouncer {
+            Some(debouncer) => debouncer.submit(value),
+            None => Self::write(&self.key, &value),
+        }
+    }
+
+    /// Returns the stored value, if any.
+    fn load(&self) -> Option<Vec<u8>> {
+        match GattServer::with_storage(|storage| storage.get(&self.key)) {
+            Ok(value) => value,
+            Err(error) => {
+                warn!("Cannot read persisted value {}: {}.", self.key, error);
+                None
+            }
+        }
+    }
+
+    fn write(key: &str, value: &[u8]) {
+        debug!("Persisting value {:02X?} at key {}.", value, key);
+
+        if let Err(error) = GattServer::with_storage(|storage| storage.set(key, value)) {
+            warn!("Cannot persist value {}: {}.", key, error);
+        }
+    }
+}
+
+impl Characteristic {
+    /// Persists the value of this [`Characteristic`] under the given key, so that it survives reboots.
+    ///
+    /// The stored value, if any, replaces the initial value when the characteristic is registered.
+    /// Every value written by a client is stored, with the [`Storage`] set with [`GattServer::storage`].
+    /// Long values, written in several parts, are stored once the client executes the writes.
+    /// Values set with [`Characteristic::set_value`] are not stored.
+    ///
+    /// Keys are at most 15 characters long, and must be unique across the server.
+    ///
+    /// [`Storage`]: crate::utilities::Storage
+    pub fn persist<S: Into<String>>(&mut self, key: S) -> &mut Self {
+        let key = key.into();
+
+        if !is_valid_key(&key) {
+            warn!(
+                "Invalid persistence key \"{}\" for characteristic {}. Keys must be between 1 and {} characters long.",
+                key, self, MAX_KEY_LENGTH
+            );
+            return self;
+        }
+
+        self.persistence = Some(Persistence {
+            key,
+            debouncer: None,
+        });
+
+        self
+    }
+
+    /// Waits for the client to stop writing for the given delay before storing the value.
+    ///
+    /// This limits flash wear when a value changes often, for example while the user drags a slider.
+    /// Values written during the delay are lost if the device reboots.
+    pub fn persist_debounce(&mut self, delay: Duration) -> &mut Self {
+        let Some(persistence) = &mut self.persistence else {
+            warn!(
+                "Characteristic {} is not persisted. Ignoring debounce delay.",
+                self
+            );
+            return self;
+        };
+
+        let key = persistence.key.clone();
+        persistence.debouncer = Some(Debouncer::new(delay, move |value: Vec<u8>| {
+            Persistence::write(&key, &value);
+        }));
+        self
+    }
+
+    /// Replaces the initial value with the persisted one, if any.
+    pub(crate) fn load_persisted_value(&mut self) {
+        let Some(value) = self
+            .persistence
+            .as_ref()
+            .and_then(Persistence::load)
+            .filter(|value| !value.is_empty())
+        else {
+            return;
+        };
+
+        if let Some(max_value_length) = self.max_value_length {
+            if value.len() > max_value_length as usize {
+                warn!(
+                    "Persisted value of {} is longer than {} bytes. Ignoring it.",
+                    self, max_value_length
+                );
+                return;
+            }
+        }
+
+        debug!("Loaded persisted value {:02X?} for {}.", value, self);
+
+        self.internal_value = value;
+        if let AttributeControl::AutomaticResponse(_) = self.control {
+            self.control = AttributeControl::AutomaticResponse(self.internal_value.clone());
+        }
+    }
+}
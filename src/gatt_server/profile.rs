@@ -1,6 +1,9 @@
 use std::sync::{Arc, RwLock};
 
-use crate::gatt_server::service::Service;
+use crate::{
+    gatt_server::{characteristic::Characteristic, descriptor::Descriptor, service::Service},
+    utilities::BleUuid,
+};
 use esp_idf_sys::*;
 use log::debug;
 
@@ -18,6 +21,16 @@ pub struct Profile {
     pub(crate) services: Vec<Arc<RwLock<Service>>>,
     pub(crate) identifier: u16,
     pub(crate) interface: Option<u8>,
+    pub(crate) prepared_writes: Vec<PreparedWrite>,
+}
+
+/// A part of a long value written by a client, queued until the client executes the prepared writes.
+#[derive(Debug, Clone)]
+pub(crate) struct PreparedWrite {
+    pub(crate) conn_id: u16,
+    pub(crate) handle: u16,
+    pub(crate) offset: u16,
+    pub(crate) value: Vec<u8>,
 }
 
 impl Profile {
@@ -29,6 +42,7 @@ impl Profile {
             services: Vec::new(),
             identifier,
             interface: None,
+            prepared_writes: Vec::new(),
         }
     }
 
@@ -76,6 +90,99 @@ impl Profile {
         None
     }
 
+    /// Returns a path that identifies the characteristic of a descriptor, and does not depend on attribute handles.
+    ///
+    /// The path is made of the profile identifier, and the UUIDs of the service and the characteristic,
+    /// each followed by its index among the attributes with the same UUID.
+    pub(crate) fn attribute_path(
+        &self,
+        service: &Arc<RwLock<Service>>,
+        descriptor: &Arc<RwLock<Descriptor>>,
+    ) -> Option<String> {
+        let service_uuid = service.read().unwrap().uuid;
+        let service_instance = self
+            .services
+            .iter()
+            .take_while(|other| !Arc::ptr_eq(other, service))
+            .filter(|other| other.read().unwrap().uuid == service_uuid)
+            .count();
+
+        let service = service.read().unwrap();
+        let position = service.characteristics.iter().position(|characteristic| {
+            characteristic
+                .read()
+                .unwrap()
+                .descriptors
+                .iter()
+                .any(|other| Arc::ptr_eq(other, descriptor))
+        })?;
+
+        let characteristic_uuid = service.characteristics[position].read().unwrap().uuid;
+        let characteristic_instance = service.characteristics[..position]
+            .iter()
+            .filter(|other| other.read().unwrap().uuid == characteristic_uuid)
+            .count();
+
+        Some(format!(
+            "{}/{}#{}/{}#{}",
+            self.identifier,
+            service_uuid,
+            service_instance,
+            characteristic_uuid,
+            characteristic_instance
+        ))
+    }
+
+    /// Returns the characteristic that contains a descriptor.
+    pub(crate) fn characteristic_of(
+        service: &Arc<RwLock<Service>>,
+        descriptor: &Arc<RwLock<Descriptor>>,
+    ) -> Option<Arc<RwLock<Characteristic>>> {
+        service
+            .read()
+            .unwrap()
+            .characteristics
+            .iter()
+            .find(|characteristic| {
+                characteristic
+                    .read()
+                    .unwrap()
+                    .descriptors
+                    .iter()
+                    .any(|other| Arc::ptr_eq(other, descriptor))
+            })
+            .cloned()
+    }
+
+    /// Sets the value of an aggregate format descriptor to the handles of the presentation formats
+    /// of its characteristic, which are registered before it.
+    pub(crate) fn fill_aggregate_format(
+        service: &Arc<RwLock<Service>>,
+        descriptor: &Arc<RwLock<Descriptor>>,
+    ) {
+        let Some(characteristic) = Self::characteristic_of(service, descriptor) else {
+            return;
+        };
+
+        let handles: Vec<u8> = characteristic
+            .read()
+            .unwrap()
+            .descriptors
+            .iter()
+            .filter_map(|other| {
+                let other = other.read().unwrap();
+                if other.uuid == BleUuid::Uuid16(0x2904) {
+                    other.attribute_handle
+                } else {
+                    None
+                }
+            })
+            .flat_map(u16::to_le_bytes)
+            .collect();
+
+        descriptor.write().unwrap().set_value(handles);
+    }
+
     pub(crate) fn register_self(&self) {
         debug!("Registering {}.", self);
         unsafe { esp_nofail!(esp_ble_gatts_app_register(self.identifier)) };
@@ -0,0 +1,204 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use log::{debug, info, warn};
+
+use crate::{gatt_server::GLOBAL_GATT_SERVER, utilities::Connection};
+
+/// Represents the proximity of a connected device, as estimated by an [`RssiMonitor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Proximity {
+    /// The smoothed RSSI rose above the "near" threshold.
+    Near,
+    /// The smoothed RSSI fell below the "far" threshold.
+    Far,
+}
+
+/// Periodically reads the RSSI of a [`Connection`], smoothing the readings and reporting proximity changes.
+///
+/// The readings are smoothed with an exponential moving average.
+/// Proximity changes use two thresholds, so that a signal hovering around a single value
+/// does not trigger a burst of events.
+///
+/// The monitor stops by itself when the connection is closed.
+#[derive(Clone)]
+pub struct RssiMonitor {
+    connection: Connection,
+    interval: Duration,
+    smoothing: f32,
+    thresholds: (i8, i8),
+    sample_callback: Option<Arc<dyn Fn(i8) + Send + Sync>>,
+    proximity_callback: Option<Arc<dyn Fn(Proximity) + Send + Sync>>,
+    running: Arc<AtomicBool>,
+}
+
+impl RssiMonitor {
+    /// Creates a new [`RssiMonitor`] for the given [`Connection`].
+    ///
+    /// By default, the RSSI is read every second, with a smoothing factor of 0.25,
+    /// a "near" threshold of -60 dBm and a "far" threshold of -80 dBm.
+    #[must_use]
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            interval: Duration::from_secs(1),
+            smoothing: 0.25,
+            thresholds: (-60, -80),
+            sample_callback: None,
+            proximity_callback: None,
+            running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Sets the interval between two readings.
+    pub fn interval(&mut self, interval: Duration) -> &mut Self {
+        if interval.is_zero() {
+            warn!("The RSSI monitor interval cannot be zero. Ignoring interval.");
+            return self;
+        }
+
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the smoothing factor of the exponential moving average, between 0 (excluded) and 1.
+    ///
+    /// Lower values smooth more, but react more slowly. A value of 1 disables smoothing.
+    pub fn smoothing(&mut self, factor: f32) -> &mut Self {
+        if factor.is_nan() || factor <= 0.0 || factor > 1.0 {
+            warn!("The RSSI smoothing factor must be between 0 (excluded) and 1. Ignoring smoothing factor.");
+            return self;
+        }
+
+        self.smoothing = factor;
+        self
+    }
+
+    /// Sets the "near" and "far" thresholds, in dBm.
+    ///
+    /// The "near" threshold must be greater than the "far" threshold.
+    pub fn proximity_thresholds(&mut self, near: i8, far: i8) -> &mut Self {
+        if near <= far {
+            warn!("The \"near\" RSSI threshold must be greater than the \"far\" threshold. Ignoring thresholds.");
+            return self;
+        }
+
+        self.thresholds = (near, far);
+        self
+    }
+
+    /// Sets a callback that is called with every smoothed reading, in dBm.
+    pub fn on_sample(&mut self, callback: impl Fn(i8) + Send + Sync + 'static) -> &mut Self {
+        self.sample_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when the estimated [`Proximity`] changes.
+    ///
+    /// The callback is called once the first time a threshold is crossed.
+    pub fn on_proximity_change(
+        &mut self,
+        callback: impl Fn(Proximity) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.proximity_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Returns whether the monitor is running.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Starts the monitor in a new thread.
+    pub fn start(&mut self) {
+        if self.running.swap(true, Ordering::Relaxed) {
+            warn!("RSSI monitor for {} already running.", self.connection);
+            return;
+        }
+
+        info!("Starting RSSI monitor for {}.", self.connection);
+
+        let monitor = self.clone();
+        std::thread::spawn(move || monitor.run());
+    }
+
+    /// Stops the monitor after the current reading.
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn run(&self) {
+        let mut smoothed: Option<f32> = None;
+        let mut proximity: Option<Proximity> = None;
+
+        while self.is_running() {
+            if !GLOBAL_GATT_SERVER
+                .lock()
+                .expect("Cannot lock global GATT server.")
+                .active_connections
+                .contains(&self.connection)
+            {
+                debug!("{} disconnected. Stopping RSSI monitor.", self.connection);
+                break;
+            }
+
+            match self.connection.read_rssi(self.interval) {
+                Ok(rssi) => {
+                    let value = smoothed.map_or(f32::from(rssi), |previous| {
+                        self.smoothing * f32::from(rssi) + (1.0 - self.smoothing) * previous
+                    });
+                    smoothed = Some(value);
+
+                    if let Some(callback) = &self.sample_callback {
+                        callback(value.round() as i8);
+                    }
+
+                    let (near, far) = self.thresholds;
+                    let new_proximity = if value >= f32::from(near) {
+                        Some(Proximity::Near)
+                    } else if value <= f32::from(far) {
+                        Some(Proximity::Far)
+                    } else {
+                        proximity
+                    };
+
+                    if new_proximity != proximity {
+                        proximity = new_proximity;
+
+                        if let (Some(callback), Some(proximity)) =
+                            (&self.proximity_callback, proximity)
+                        {
+                            callback(proximity);
+                        }
+                    }
+                }
+                Err(error) => {
+                    debug!("Cannot read RSSI of {}: {}.", self.connection, error);
+                }
+            }
+
+            std::thread::sleep(self.interval);
+        }
+
+        self.stop();
+    }
+}
+
+impl std::fmt::Debug for RssiMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RssiMonitor")
+            .field("connection", &self.connection)
+            .field("interval", &self.interval)
+            .field("smoothing", &self.smoothing)
+            .field("thresholds", &self.thresholds)
+            .field("running", &self.is_running())
+            .finish_non_exhaustive()
+    }
+}
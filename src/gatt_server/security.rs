@@ -0,0 +1,104 @@
+use esp_idf_sys::*;
+use log::{debug, warn};
+
+use crate::{
+    gatt_server::GattServer,
+    utilities::{LocalAddress, SecurityConfig},
+};
+
+impl GattServer {
+    /// Sets the [`SecurityConfig`] used when pairing with clients.
+    ///
+    /// The configuration must be set before starting the server.
+    pub fn security(&mut self, config: SecurityConfig) -> &mut Self {
+        if self.started {
+            warn!("Cannot change the security configuration after the server has started.");
+            return self;
+        }
+
+        self.security_config = config;
+        self
+    }
+
+    /// Configures the Security Manager in the stack.
+    pub(crate) fn configure_security(&self) {
+        let config = self.security_config;
+        debug!("Configuring security: {:?}.", config);
+
+        let mut initiator_keys = u8::from(config.initiator_keys);
+        let mut responder_keys = u8::from(config.responder_keys);
+
+        // Bonded peers need the identity key to resolve our private address.
+        if matches!(self.local_address, LocalAddress::ResolvablePrivate(_)) {
+            initiator_keys |= ESP_BLE_ID_KEY_MASK as u8;
+            responder_keys |= ESP_BLE_ID_KEY_MASK as u8;
+        }
+
+        let mut authentication_requirements = config.authentication_requirements();
+        let mut io_capabilities = esp_ble_io_cap_t::from(config.io_capabilities);
+        let mut min_key_size = config.min_key_size;
+        let mut max_key_size = config.max_key_size;
+        let mut only_accept_specified = if config.secure_connections_only {
+            ESP_BLE_ONLY_ACCEPT_SPECIFIED_AUTH_ENABLE
+        } else {
+            ESP_BLE_ONLY_ACCEPT_SPECIFIED_AUTH_DISABLE
+        } as u8;
+        let mut oob_support = if config.oob {
+            ESP_BLE_OOB_ENABLE
+        } else {
+            ESP_BLE_OOB_DISABLE
+        } as u8;
+
+        unsafe {
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_AUTHEN_REQ_MODE,
+                &mut authentication_requirements,
+            );
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_IOCAP_MODE,
+                &mut io_capabilities,
+            );
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_MIN_KEY_SIZE,
+                &mut min_key_size,
+            );
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_MAX_KEY_SIZE,
+                &mut max_key_size,
+            );
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_SET_INIT_KEY,
+                &mut initiator_keys,
+            );
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_SET_RSP_KEY,
+                &mut responder_keys,
+            );
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_ONLY_ACCEPT_SPECIFIED_SEC_AUTH,
+                &mut only_accept_specified,
+            );
+            Self::set_security_parameter(
+                esp_ble_sm_param_t_ESP_BLE_SM_OOB_SUPPORT,
+                &mut oob_support,
+            );
+
+            if let Some(mut passkey) = config.static_passkey {
+                Self::set_security_parameter(
+                    esp_ble_sm_param_t_ESP_BLE_SM_SET_STATIC_PASSKEY,
+                    &mut passkey,
+                );
+            }
+        }
+    }
+
+    /// Sets a single Security Manager parameter.
+    #[allow(clippy::cast_possible_truncation)]
+    unsafe fn set_security_parameter<T>(parameter: esp_ble_sm_param_t, value: &mut T) {
+        esp_nofail!(esp_ble_gap_set_security_param(
+            parameter,
+            std::ptr::addr_of_mut!(*value).cast(),
+            std::mem::size_of::<T>() as u8
+        ));
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
-    gatt_server::characteristic::Characteristic, gatt_server::descriptor::Descriptor,
-    leaky_box_raw, utilities::BleUuid,
+    gatt_server::authorization::{AccessRequest, Authorizer},
+    gatt_server::characteristic::Characteristic,
+    gatt_server::descriptor::Descriptor,
+    leaky_box_raw,
+    utilities::BleUuid,
 };
 use esp_idf_sys::*;
 use log::debug;
@@ -17,6 +20,7 @@ pub struct Service {
     pub(crate) characteristics: Vec<Arc<RwLock<Characteristic>>>,
     primary: bool,
     pub(crate) handle: Option<u16>,
+    pub(crate) authorizer: Option<Authorizer>,
 }
 
 impl Service {
@@ -29,6 +33,7 @@ impl Service {
             characteristics: Vec::new(),
             primary: false,
             handle: None,
+            authorizer: None,
         }
     }
 
@@ -54,6 +59,25 @@ impl Service {
         self
     }
 
+    /// Sets a callback that decides whether a client can read or write the attributes of this [`Service`].
+    ///
+    /// The callback applies to all the characteristics that do not have their own authorization callback,
+    /// and overrides the one set on the [`GattServer`]. Denied requests are answered with an
+    /// "insufficient authorization" error.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    ///
+    /// [`GattServer`]: crate::gatt_server::GattServer
+    pub fn authorize(
+        &mut self,
+        callback: impl Fn(&AccessRequest) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.authorizer = Some(Authorizer::new(callback));
+        self
+    }
+
     /// Returns a reference to the built [`Service`] behind an `Arc` and an `RwLock`.
     ///
     /// The returned value can be passed to any function of this crate that expects a [`Service`].
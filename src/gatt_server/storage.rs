@@ -0,0 +1,81 @@
+use std::sync::Mutex;
+
+use log::{debug, warn};
+
+use crate::{
+    gatt_server::GattServer,
+    utilities::{MemoryStorage, NvsStorage, Storage},
+};
+
+/// The namespace of the default NVS storage.
+const DEFAULT_NAMESPACE: &str = "ble";
+
+/// The storage used to persist the state of the server, opened on first use if not set.
+static STORAGE: Mutex<Option<Box<dyn Storage>>> = Mutex::new(None);
+
+impl GattServer {
+    /// Sets the [`Storage`] used to persist CCCD subscriptions, persisted values and the random static address.
+    ///
+    /// By default, the `"ble"` namespace of the default NVS partition is used. If the partition is not
+    /// available, the values are kept in RAM and lost on reboot.
+    ///
+    /// The storage must be set before starting the server.
+    pub fn storage(&mut self, storage: impl Storage + 'static) -> &mut Self {
+        if self.started {
+            warn!("Cannot change the storage after the server has started.");
+            return self;
+        }
+
+        *STORAGE.lock().unwrap() = Some(Box::new(storage));
+        self
+    }
+
+    /// Sets whether the NVS partition is erased when it cannot be initialised, for example
+    /// because it is full or was written by a newer ESP-IDF version. Enabled by default.
+    ///
+    /// When disabled, the application must initialise NVS before starting the server.
+    /// If NVS cannot be initialised, the values are kept in RAM and lost on reboot.
+    ///
+    /// The behaviour must be set before starting the server.
+    pub fn erase_nvs_on_failure(&mut self, erase: bool) -> &mut Self {
+        if self.started {
+            warn!("Cannot change the NVS erase behaviour after the server has started.");
+            return self;
+        }
+
+        self.erase_nvs_on_failure = erase;
+        self
+    }
+
+    /// Runs a function on the storage, opening the default one if none has been set.
+    pub(crate) fn with_storage<R>(function: impl FnOnce(&mut dyn Storage) -> R) -> R {
+        let mut storage = STORAGE.lock().unwrap();
+
+        let storage = storage.get_or_insert_with(|| match NvsStorage::new(DEFAULT_NAMESPACE) {
+            Ok(storage) => {
+                debug!("Using the default NVS partition for storage.");
+                Box::new(storage)
+            }
+            Err(error) => {
+                warn!(
+                    "Cannot open the default NVS partition ({}), set another storage if the application \
+                     has taken it. Values are kept in RAM and will be lost on reboot.",
+                    error
+                );
+                Box::new(MemoryStorage::new())
+            }
+        });
+
+        function(storage.as_mut())
+    }
+
+    /// Keeps the values in RAM, unless a storage has been set, because NVS is not available.
+    pub(crate) fn use_memory_storage() {
+        let mut storage = STORAGE.lock().unwrap();
+
+        if storage.is_none() {
+            warn!("Using RAM for storage: values will be lost on reboot.");
+            *storage = Some(Box::new(MemoryStorage::new()));
+        }
+    }
+}
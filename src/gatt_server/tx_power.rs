@@ -0,0 +1,142 @@
+use esp_idf_sys::*;
+use log::{debug, warn};
+
+use crate::{
+    gatt_server::GattServer,
+    utilities::{AdvertisementData, BleUuid, TxPowerLevel, TxPowerType},
+};
+
+/// The maximum length of a legacy advertisement or scan response payload.
+const MAX_LEGACY_DATA_LENGTH: usize = 31;
+
+impl GattServer {
+    /// Sets the transmit power level for the given activity.
+    ///
+    /// Levels set before starting the server are applied as soon as the controller is enabled.
+    /// When the advertising power changes, the Tx Power Level reported in the advertisement is updated too.
+    pub fn tx_power(&mut self, power_type: TxPowerType, level: TxPowerLevel) -> &mut Self {
+        if matches!(power_type, TxPowerType::Connection(handle) if handle > 8) {
+            warn!("Connection handles range from 0 to 8. Ignoring transmit power level.");
+            return self;
+        }
+
+        self.tx_power_levels
+            .retain(|(existing_type, _)| *existing_type != power_type);
+        self.tx_power_levels.push((power_type, level));
+
+        if self.started {
+            Self::apply_tx_power(power_type, level);
+
+            if self.advertisement_configured
+                && matches!(power_type, TxPowerType::Advertising | TxPowerType::Default)
+            {
+                self.configure_advertisement_data();
+            }
+        }
+
+        self
+    }
+
+    /// Applies all the configured transmit power levels to the controller.
+    pub(crate) fn apply_tx_power_levels(&self) {
+        self.tx_power_levels
+            .iter()
+            .for_each(|(power_type, level)| Self::apply_tx_power(*power_type, *level));
+    }
+
+    fn apply_tx_power(power_type: TxPowerType, level: TxPowerLevel) {
+        debug!("Setting {:?} transmit power to {}.", power_type, level);
+
+        let result = unsafe { esp!(esp_ble_tx_power_set(power_type.into(), level.into())) };
+        if let Err(error) = result {
+            warn!(
+                "Cannot set {:?} transmit power to {}: {}.",
+                power_type, level, error
+            );
+        }
+    }
+
+    /// Encodes a legacy payload, reporting the actual advertising power in the Tx Power Level AD structure.
+    ///
+    /// The stack does not know the power level set in the controller,
+    /// so payloads that include it are built here and configured as raw data.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub(crate) fn raw_advertisement_payload(&self, data: &esp_ble_adv_data_t) -> Vec<u8> {
+        let mut payload = AdvertisementData::new();
+
+        if data.flag != 0 {
+            payload = payload.flags(data.flag);
+        }
+
+        if data.include_txpower {
+            let level =
+                TxPowerLevel::current(TxPowerType::Advertising).map_or(0, TxPowerLevel::dbm);
+            payload = payload.tx_power(level);
+        }
+
+        if data.appearance != 0 {
+            payload = payload.raw(0x19, (data.appearance as u16).to_le_bytes().to_vec());
+        }
+
+        if data.min_interval > 0 && data.max_interval > 0 {
+            let mut interval = (data.min_interval as u16).to_le_bytes().to_vec();
+            interval.extend((data.max_interval as u16).to_le_bytes());
+            payload = payload.raw(0x12, interval);
+        }
+
+        if data.manufacturer_len > 0 && !data.p_manufacturer_data.is_null() {
+            let manufacturer_data = unsafe {
+                std::slice::from_raw_parts(data.p_manufacturer_data, data.manufacturer_len as usize)
+            };
+            payload = payload.raw(0xFF, manufacturer_data.to_vec());
+        }
+
+        if data.service_data_len > 0 && !data.p_service_data.is_null() {
+            let service_data = unsafe {
+                std::slice::from_raw_parts(data.p_service_data, data.service_data_len as usize)
+            };
+            payload = payload.raw(0x16, service_data.to_vec());
+        }
+
+        if data.service_uuid_len > 0 && !data.p_service_uuid.is_null() {
+            let service_uuids = unsafe {
+                std::slice::from_raw_parts(data.p_service_uuid, data.service_uuid_len as usize)
+            };
+            for uuid in service_uuids.chunks_exact(16) {
+                let mut bytes = [0u8; 16];
+                bytes.copy_from_slice(uuid);
+                payload = payload.service_uuid(BleUuid::Uuid128(bytes));
+            }
+        }
+
+        // Characteristics broadcast by the clients, through their SCCD.
+        if !data.set_scan_rsp {
+            for (uuid, value) in self.broadcast_service_data() {
+                payload = payload.service_data(uuid, value);
+            }
+        }
+
+        // The name takes the remaining space, and is shortened if it does not fit.
+        if data.include_name {
+            let name = self.device_name.trim_end_matches('\0').as_bytes();
+            let available = MAX_LEGACY_DATA_LENGTH.saturating_sub(payload.to_bytes().len() + 2);
+
+            if name.len() <= available {
+                payload = payload.raw(0x09, name.to_vec());
+            } else if available > 0 {
+                payload = payload.raw(0x08, name[..available].to_vec());
+            }
+        }
+
+        let payload = payload.to_bytes();
+        if payload.len() > MAX_LEGACY_DATA_LENGTH {
+            warn!(
+                "Advertisement payload is {} bytes long, but the maximum length is {} bytes.",
+                payload.len(),
+                MAX_LEGACY_DATA_LENGTH
+            );
+        }
+
+        payload
+    }
+}
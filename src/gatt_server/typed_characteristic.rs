@@ -0,0 +1,160 @@
+use std::{
+    marker::PhantomData,
+    sync::{Arc, RwLock},
+};
+
+use esp_idf_sys::esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN;
+use log::warn;
+
+use crate::{gatt_server::Characteristic, utilities::GattValue};
+
+/// A [`Characteristic`] whose value has the type `T`.
+///
+/// Values are encoded and decoded with the [`GattValue`] implementation of `T`.
+/// Writes that cannot be decoded are rejected with an "invalid attribute length" error,
+/// before reaching the write callback.
+///
+/// # Example
+///
+/// ```ignore
+/// let temperature = TypedCharacteristic::<i16>::new(
+///     Characteristic::new(BleUuid::Uuid16(0x2A6E))
+///         .permissions(AttributePermissions::new().read().write())
+///         .properties(CharacteristicProperties::new().read().write())
+///         .build(),
+/// );
+///
+/// temperature.set(&2150).on_write(|value| info!("New temperature: {}", value));
+///
+/// let service = Service::new(BleUuid::Uuid16(0x181A))
+///     .characteristic(temperature.characteristic())
+///     .build();
+/// ```
+pub struct TypedCharacteristic<T: GattValue> {
+    characteristic: Arc<RwLock<Characteristic>>,
+    value_type: PhantomData<fn() -> T>,
+}
+
+impl<T: GattValue + 'static> TypedCharacteristic<T> {
+    /// Wraps a built [`Characteristic`], rejecting the writes that are not valid values of `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the characteristic's lock is poisoned.
+    #[must_use]
+    pub fn new(characteristic: Arc<RwLock<Characteristic>>) -> Self {
+        {
+            let mut characteristic = characteristic.write().unwrap();
+
+            if let Some(length) = T::LENGTH {
+                if characteristic.max_value_length.is_none() {
+                    #[allow(clippy::cast_possible_truncation)]
+                    characteristic.max_value_length(length as u16);
+                }
+            }
+
+            characteristic.validate_writes(|bytes| {
+                T::decode(bytes)
+                    .map(|_| ())
+                    .ok_or(esp_gatt_status_t_ESP_GATT_INVALID_ATTR_LEN)
+            });
+        }
+
+        Self {
+            characteristic,
+            value_type: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped [`Characteristic`], to be added to a [`Service`].
+    ///
+    /// [`Service`]: crate::gatt_server::Service
+    #[must_use]
+    pub const fn characteristic(&self) -> &Arc<RwLock<Characteristic>> {
+        &self.characteristic
+    }
+
+    /// Sets the value of the [`Characteristic`].
+    ///
+    /// Sends notifications and indications to all subscribed clients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the encoded value is too long, or if the characteristic's lock is poisoned.
+    pub fn set(&self, value: &T) -> &Self {
+        self.characteristic
+            .write()
+            .unwrap()
+            .set_value(value.encode());
+        self
+    }
+
+    /// Returns the current value of the [`Characteristic`], or `None` if it cannot be decoded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the characteristic's lock is poisoned.
+    #[must_use]
+    pub fn get(&self) -> Option<T> {
+        T::decode(&self.characteristic.read().unwrap().internal_value)
+    }
+
+    /// Sets the read callback of the [`Characteristic`].
+    ///
+    /// The callback returns the value to be put into the response to the read request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the characteristic's lock is poisoned.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_read(&self, callback: impl Fn() -> T + Send + Sync + 'static) -> &Self {
+        self.characteristic
+            .write()
+            .unwrap()
+            .on_read(move |_| callback().encode());
+        self
+    }
+
+    /// Sets the write callback of the [`Characteristic`].
+    ///
+    /// The callback receives the decoded value. Malformed values are rejected before reaching it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the characteristic's lock is poisoned.
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn on_write(&self, callback: impl Fn(T) + Send + Sync + 'static) -> &Self {
+        self.characteristic
+            .write()
+            .unwrap()
+            .on_write(move |bytes, _| match T::decode(&bytes) {
+                Some(value) => callback(value),
+                None => warn!("Cannot decode written value {:02X?}.", bytes),
+            });
+        self
+    }
+}
+
+impl<T: GattValue> Clone for TypedCharacteristic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            characteristic: self.characteristic.clone(),
+            value_type: PhantomData,
+        }
+    }
+}
+
+impl<T: GattValue> std::fmt::Debug for TypedCharacteristic<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedCharacteristic")
+            .field("characteristic", &self.characteristic)
+            .field("value_type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
@@ -15,8 +15,13 @@
 // In ESP32-S2, the Bluetooth controller is not present.
 // Completely disable this crate.
 
-#[cfg(not(esp32s2))]
+// On other targets than ESP-IDF, only the value encoders and the storage backends are built, to be used in tests on the host.
+
+#[cfg(all(not(esp32s2), target_os = "espidf"))]
 pub mod gatt_server;
 
 #[cfg(not(esp32s2))]
 pub mod utilities;
+
+#[cfg(not(esp32s2))]
+pub mod services;
@@ -0,0 +1,463 @@
+// The encoding of the values does not depend on ESP-IDF, so that it can be tested on the host.
+
+#[cfg(target_os = "espidf")]
+use std::sync::{Arc, RwLock};
+
+#[cfg(target_os = "espidf")]
+use log::{debug, warn};
+
+#[cfg(target_os = "espidf")]
+use crate::{
+    gatt_server::{Characteristic, Service},
+    utilities::{AttributePermissions, BleUuid, CharacteristicProperties},
+};
+
+/// A typical discharge curve of a single-cell lithium-ion battery, in millivolts and percent.
+///
+/// Pass it to [`Battery::set_level_from_voltage`] when no better curve is available.
+pub const LI_ION_DISCHARGE_CURVE: &[(u32, u8)] = &[
+    (3300, 0),
+    (3500, 5),
+    (3600, 10),
+    (3700, 30),
+    (3750, 40),
+    (3800, 50),
+    (3850, 60),
+    (3900, 70),
+    (4000, 80),
+    (4100, 90),
+    (4200, 100),
+];
+
+/// The power state of a battery.
+///
+/// Unknown fields are set to `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PowerState {
+    /// Whether the battery is present.
+    pub present: bool,
+    /// Whether a wired external power source is connected.
+    pub external_power: Option<bool>,
+    /// Whether the battery is charging, or discharging.
+    pub charging: Option<bool>,
+    /// Whether the battery level is critically low.
+    pub critical: Option<bool>,
+}
+
+impl PowerState {
+    /// Encodes the value of the Battery Power State characteristic (`0x2A1A`).
+    fn encode_legacy(self) -> u8 {
+        let state = |value: Option<bool>| match value {
+            None => 0b00,
+            Some(false) => 0b10,
+            Some(true) => 0b11,
+        };
+
+        let present = if self.present { 0b11 } else { 0b10 };
+        let discharging = state(self.charging.map(|charging| !charging));
+        let charging = state(self.charging);
+        let level = state(self.critical);
+
+        present | discharging << 2 | charging << 4 | level << 6
+    }
+
+    /// Encodes the power state field of the Battery Level Status characteristic (`0x2BED`).
+    fn encode(self) -> u16 {
+        let external_power = match self.external_power {
+            Some(false) => 0,
+            Some(true) => 1,
+            None => 2,
+        };
+
+        let charge_state = match self.charging {
+            None => 0,
+            Some(true) => 1,
+            Some(false) => 2,
+        };
+
+        let charge_level = match self.critical {
+            None => 0,
+            Some(false) => 1,
+            Some(true) => 3,
+        };
+
+        u16::from(self.present)
+            | external_power << 1
+            | 2 << 3 // Wireless external power: unknown.
+            | charge_state << 5
+            | charge_level << 7
+    }
+}
+
+/// The Battery service, with the `0x180F` UUID.
+///
+/// The service exposes the Battery Level characteristic, which clients can read and subscribe to.
+/// The Battery Level Status and Battery Power State characteristics are optional.
+///
+/// The value is updated with [`Battery::set_level`], and notified to the subscribed clients
+/// only when it changes.
+///
+/// # Example
+///
+/// ```ignore
+/// let battery = Battery::new(100).level_status().clone();
+///
+/// let profile = Profile::new(0x0001)
+///     .service(&battery.build())
+///     .build();
+///
+/// // Later, from the application.
+/// battery.set_level_from_voltage(read_battery_millivolts(), LI_ION_DISCHARGE_CURVE);
+/// ```
+#[cfg(target_os = "espidf")]
+#[derive(Debug, Clone)]
+pub struct Battery {
+    level: Arc<RwLock<Characteristic>>,
+    level_status: Option<Arc<RwLock<Characteristic>>>,
+    power_state: Option<Arc<RwLock<Characteristic>>>,
+    state: Arc<RwLock<(u8, PowerState)>>,
+}
+
+#[cfg(target_os = "espidf")]
+impl Battery {
+    /// Creates a new [`Battery`] service, with the given initial level in percent.
+    #[must_use]
+    pub fn new(level: u8) -> Self {
+        let level = level.min(100);
+
+        Self {
+            level: Self::characteristic(0x2A19, "Battery Level", vec![level]),
+            level_status: None,
+            power_state: None,
+            state: Arc::new(RwLock::new((level, PowerState::default()))),
+        }
+    }
+
+    /// Adds the Battery Level Status characteristic (`0x2BED`), which reports the power state and the level.
+    pub fn level_status(&mut self) -> &mut Self {
+        let (level, power_state) = *self.state.read().unwrap();
+
+        self.level_status = Some(Self::characteristic(
+            0x2BED,
+            "Battery Level Status",
+            encode_level_status(level, power_state),
+        ));
+        self
+    }
+
+    /// Adds the Battery Power State characteristic (`0x2A1A`), for older clients.
+    pub fn power_state(&mut self) -> &mut Self {
+        let (_, power_state) = *self.state.read().unwrap();
+
+        self.power_state = Some(Self::characteristic(
+            0x2A1A,
+            "Battery Power State",
+            vec![power_state.encode_legacy()],
+        ));
+        self
+    }
+
+    /// Returns the built Battery [`Service`] behind an `Arc` and an `RwLock`.
+    #[must_use]
+    pub fn build(&self) -> Arc<RwLock<Service>> {
+        let mut service = Service::new(BleUuid::from_uuid16(0x180F));
+        service
+            .name("Battery")
+            .primary()
+            .characteristic(&self.level);
+
+        if let Some(level_status) = &self.level_status {
+            service.characteristic(level_status);
+        }
+
+        if let Some(power_state) = &self.power_state {
+            service.characteristic(power_state);
+        }
+
+        service.build()
+    }
+
+    /// Returns the current battery level, in percent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state lock is poisoned.
+    #[must_use]
+    pub fn level(&self) -> u8 {
+        self.state.read().unwrap().0
+    }
+
+    /// Sets the battery level, in percent, and notifies the subscribed clients if it changed.
+    ///
+    /// Levels above 100 are clamped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state lock or a characteristic's lock is poisoned.
+    pub fn set_level(&self, level: u8) {
+        if level > 100 {
+            warn!("Battery level {}% is above 100%. Clamping it.", level);
+        }
+        let level = level.min(100);
+
+        let power_state = {
+            let mut state = self.state.write().unwrap();
+            if state.0 == level {
+                return;
+            }
+
+            state.0 = level;
+            state.1
+        };
+
+        debug!("Battery level changed to {}%.", level);
+        self.level.write().unwrap().set_value(vec![level]);
+
+        if let Some(level_status) = &self.level_status {
+            level_status
+                .write()
+                .unwrap()
+                .set_value(encode_level_status(level, power_state));
+        }
+    }
+
+    /// Sets the battery level from a voltage, interpolating linearly between the points of a discharge curve.
+    ///
+    /// The curve is a list of voltages, in increasing order, with the matching level in percent.
+    /// The voltage is usually read with the ADC, through a voltage divider.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state lock or a characteristic's lock is poisoned.
+    pub fn set_level_from_voltage(&self, millivolts: u32, curve: &[(u32, u8)]) {
+        let Some(level) = level_from_voltage(millivolts, curve) else {
+            warn!("Invalid discharge curve. Ignoring voltage.");
+            return;
+        };
+
+        self.set_level(level);
+    }
+
+    /// Sets the power state, and notifies the subscribed clients if it changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state lock or a characteristic's lock is poisoned.
+    pub fn set_power_state(&self, power_state: PowerState) {
+        let level = {
+            let mut state = self.state.write().unwrap();
+            if state.1 == power_state {
+                return;
+            }
+
+            state.1 = power_state;
+            state.0
+        };
+
+        debug!("Battery power state changed to {:?}.", power_state);
+
+        if let Some(level_status) = &self.level_status {
+            level_status
+                .write()
+                .unwrap()
+                .set_value(encode_level_status(level, power_state));
+        }
+
+        if let Some(characteristic) = &self.power_state {
+            characteristic
+                .write()
+                .unwrap()
+                .set_value(vec![power_state.encode_legacy()]);
+        }
+    }
+
+    fn characteristic(uuid: u16, name: &str, value: Vec<u8>) -> Arc<RwLock<Characteristic>> {
+        Characteristic::new(BleUuid::from_uuid16(uuid))
+            .name(name)
+            .permissions(AttributePermissions::new().read())
+            .properties(CharacteristicProperties::new().read().notify())
+            .set_value(value)
+            .build()
+    }
+}
+
+/// Returns the level matching a voltage on a discharge curve, or `None` if the curve is empty or unsorted.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn level_from_voltage(millivolts: u32, curve: &[(u32, u8)]) -> Option<u8> {
+    let (first, last) = (curve.first()?, curve.last()?);
+
+    if curve.windows(2).any(|points| points[0].0 > points[1].0) {
+        return None;
+    }
+
+    if millivolts <= first.0 {
+        return Some(first.1);
+    }
+
+    if millivolts >= last.0 {
+        return Some(last.1);
+    }
+
+    curve.windows(2).find_map(|points| {
+        let ((low_voltage, low_level), (high_voltage, high_level)) = (points[0], points[1]);
+
+        if millivolts < low_voltage || millivolts > high_voltage {
+            return None;
+        }
+
+        let span = i64::from(high_voltage - low_voltage).max(1);
+        let offset = i64::from(millivolts - low_voltage);
+        let delta = i64::from(high_level) - i64::from(low_level);
+
+        Some((i64::from(low_level) + delta * offset / span).clamp(0, 100) as u8)
+    })
+}
+
+/// Encodes the value of the Battery Level Status characteristic (`0x2BED`).
+fn encode_level_status(level: u8, power_state: PowerState) -> Vec<u8> {
+    // Only the battery level is present among the optional fields.
+    let flags = 0b0000_0010;
+    let [power_state_low, power_state_high] = power_state.encode().to_le_bytes();
+
+    vec![flags, power_state_low, power_state_high, level]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNKNOWN: PowerState = PowerState {
+        present: false,
+        external_power: None,
+        charging: None,
+        critical: None,
+    };
+
+    #[test]
+    fn legacy_power_state() {
+        assert_eq!(UNKNOWN.encode_legacy(), 0b00_00_00_10);
+
+        let present = PowerState {
+            present: true,
+            ..UNKNOWN
+        };
+        assert_eq!(present.encode_legacy(), 0b00_00_00_11);
+
+        let charging = |charging| {
+            PowerState {
+                charging,
+                ..UNKNOWN
+            }
+            .encode_legacy()
+        };
+        assert_eq!(charging(Some(true)), 0b00_11_10_10);
+        assert_eq!(charging(Some(false)), 0b00_10_11_10);
+
+        let critical = |critical| {
+            PowerState {
+                critical,
+                ..UNKNOWN
+            }
+            .encode_legacy()
+        };
+        assert_eq!(critical(Some(false)), 0b10_00_00_10);
+        assert_eq!(critical(Some(true)), 0b11_00_00_10);
+    }
+
+    #[test]
+    fn power_state() {
+        // Wireless external power is always unknown.
+        let wireless_unknown = 2 << 3;
+
+        assert_eq!(UNKNOWN.encode(), wireless_unknown | 2 << 1);
+
+        let present = PowerState {
+            present: true,
+            ..UNKNOWN
+        };
+        assert_eq!(present.encode(), wireless_unknown | 2 << 1 | 1);
+
+        let external_power = |external_power| {
+            PowerState {
+                external_power,
+                ..UNKNOWN
+            }
+            .encode()
+        };
+        assert_eq!(external_power(Some(false)), wireless_unknown);
+        assert_eq!(external_power(Some(true)), wireless_unknown | 1 << 1);
+
+        let charging = |charging| {
+            PowerState {
+                charging,
+                ..UNKNOWN
+            }
+            .encode()
+        };
+        assert_eq!(charging(Some(true)), wireless_unknown | 2 << 1 | 1 << 5);
+        assert_eq!(charging(Some(false)), wireless_unknown | 2 << 1 | 2 << 5);
+
+        let critical = |critical| {
+            PowerState {
+                critical,
+                ..UNKNOWN
+            }
+            .encode()
+        };
+        assert_eq!(critical(Some(false)), wireless_unknown | 2 << 1 | 1 << 7);
+        assert_eq!(critical(Some(true)), wireless_unknown | 2 << 1 | 3 << 7);
+    }
+
+    #[test]
+    fn level_status_layout() {
+        let power_state = PowerState {
+            present: true,
+            external_power: Some(true),
+            charging: Some(true),
+            critical: Some(false),
+        };
+
+        // Present, wired power, wireless power unknown, charging, good level.
+        let encoded: u16 = 1 | 1 << 1 | 2 << 3 | 1 << 5 | 1 << 7;
+        let [low, high] = encoded.to_le_bytes();
+
+        assert_eq!(
+            encode_level_status(42, power_state),
+            vec![0b0000_0010, low, high, 42]
+        );
+    }
+
+    #[test]
+    fn level_outside_curve() {
+        assert_eq!(level_from_voltage(3000, LI_ION_DISCHARGE_CURVE), Some(0));
+        assert_eq!(level_from_voltage(4500, LI_ION_DISCHARGE_CURVE), Some(100));
+    }
+
+    #[test]
+    fn level_at_curve_points() {
+        for (millivolts, level) in LI_ION_DISCHARGE_CURVE {
+            assert_eq!(
+                level_from_voltage(*millivolts, LI_ION_DISCHARGE_CURVE),
+                Some(*level)
+            );
+        }
+    }
+
+    #[test]
+    fn level_between_curve_points() {
+        assert_eq!(level_from_voltage(3650, LI_ION_DISCHARGE_CURVE), Some(20));
+        assert_eq!(level_from_voltage(4150, LI_ION_DISCHARGE_CURVE), Some(95));
+    }
+
+    #[test]
+    fn invalid_curves() {
+        assert_eq!(level_from_voltage(3700, &[]), None);
+        assert_eq!(level_from_voltage(3700, &[(4200, 100), (3300, 0)]), None);
+    }
+
+    #[test]
+    fn single_point_curve() {
+        assert_eq!(level_from_voltage(3000, &[(3700, 50)]), Some(50));
+        assert_eq!(level_from_voltage(4000, &[(3700, 50)]), Some(50));
+    }
+}
@@ -0,0 +1,310 @@
+// The encoding of the values does not depend on ESP-IDF, so that it can be tested on the host.
+
+#[cfg(target_os = "espidf")]
+use std::sync::{Arc, RwLock};
+use std::{ffi::CStr, os::raw::c_char};
+
+#[cfg(target_os = "espidf")]
+use esp_idf_sys::*;
+use log::warn;
+
+#[cfg(target_os = "espidf")]
+use crate::{
+    gatt_server::{Characteristic, Service},
+    utilities::{AttributePermissions, BleUuid, CharacteristicProperties},
+};
+
+/// The organisation that assigned the vendor identifier of a Plug and Play ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendorIdSource {
+    /// The Bluetooth SIG assigned the identifier, from the company identifiers list.
+    BluetoothSig = 0x01,
+    /// The USB Implementer's Forum assigned the identifier.
+    UsbImplementersForum = 0x02,
+}
+
+/// A builder for the Device Information service, with the `0x180A` UUID.
+///
+/// Only the characteristics that are set are included in the service.
+/// All of them are read-only.
+///
+/// # Example
+///
+/// ```ignore
+/// let device_information = DeviceInformation::new()
+///     .manufacturer_name("ACME")
+///     .app_descriptor()
+///     .system_id_from_mac()
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInformation {
+    manufacturer_name: Option<String>,
+    model_number: Option<String>,
+    serial_number: Option<String>,
+    hardware_revision: Option<String>,
+    firmware_revision: Option<String>,
+    software_revision: Option<String>,
+    system_id: Option<[u8; 8]>,
+    pnp_id: Option<[u8; 7]>,
+}
+
+impl DeviceInformation {
+    /// Creates a new, empty [`DeviceInformation`] builder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the Manufacturer Name String (`0x2A29`).
+    pub fn manufacturer_name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.manufacturer_name = Some(name.into());
+        self
+    }
+
+    /// Sets the Model Number String (`0x2A24`).
+    pub fn model_number<S: Into<String>>(&mut self, model_number: S) -> &mut Self {
+        self.model_number = Some(model_number.into());
+        self
+    }
+
+    /// Sets the Serial Number String (`0x2A25`).
+    pub fn serial_number<S: Into<String>>(&mut self, serial_number: S) -> &mut Self {
+        self.serial_number = Some(serial_number.into());
+        self
+    }
+
+    /// Sets the Hardware Revision String (`0x2A27`).
+    pub fn hardware_revision<S: Into<String>>(&mut self, revision: S) -> &mut Self {
+        self.hardware_revision = Some(revision.into());
+        self
+    }
+
+    /// Sets the Firmware Revision String (`0x2A26`).
+    pub fn firmware_revision<S: Into<String>>(&mut self, revision: S) -> &mut Self {
+        self.firmware_revision = Some(revision.into());
+        self
+    }
+
+    /// Sets the Software Revision String (`0x2A28`).
+    pub fn software_revision<S: Into<String>>(&mut self, revision: S) -> &mut Self {
+        self.software_revision = Some(revision.into());
+        self
+    }
+
+    /// Sets the System ID (`0x2A23`), made of a 40-bit manufacturer-defined identifier
+    /// and a 24-bit organisationally unique identifier.
+    pub fn system_id(&mut self, manufacturer_identifier: u64, oui: u32) -> &mut Self {
+        if manufacturer_identifier >= 1 << 40 || oui >= 1 << 24 {
+            warn!("Invalid System ID. Ignoring it.");
+            return self;
+        }
+
+        let mut system_id = [0u8; 8];
+        system_id[..5].copy_from_slice(&manufacturer_identifier.to_le_bytes()[..5]);
+        system_id[5..].copy_from_slice(&oui.to_le_bytes()[..3]);
+
+        self.system_id = Some(system_id);
+        self
+    }
+
+    /// Sets the System ID (`0x2A23`) from the Bluetooth MAC address of the device.
+    ///
+    /// The MAC address is extended to an EUI-64 by inserting `0xFFFE` after its OUI,
+    /// as recommended by the specification.
+    #[cfg(target_os = "espidf")]
+    pub fn system_id_from_mac(&mut self) -> &mut Self {
+        let mut mac = [0u8; 6];
+        if let Err(error) =
+            unsafe { esp!(esp_read_mac(mac.as_mut_ptr(), esp_mac_type_t_ESP_MAC_BT)) }
+        {
+            warn!("Cannot read the Bluetooth MAC address: {}.", error);
+            return self;
+        }
+
+        self.system_id_from_address(mac)
+    }
+
+    fn system_id_from_address(&mut self, address: [u8; 6]) -> &mut Self {
+        let [oui_0, oui_1, oui_2, nic_0, nic_1, nic_2] = address;
+
+        self.system_id(
+            u64::from_be_bytes([0, 0, 0, 0xFF, 0xFE, nic_0, nic_1, nic_2]),
+            u32::from_be_bytes([0, oui_0, oui_1, oui_2]),
+        )
+    }
+
+    /// Sets the Plug and Play ID (`0x2A50`), which identifies the product to the host operating system.
+    pub fn pnp_id(
+        &mut self,
+        source: VendorIdSource,
+        vendor_id: u16,
+        product_id: u16,
+        product_version: u16,
+    ) -> &mut Self {
+        let mut pnp_id = [source as u8, 0, 0, 0, 0, 0, 0];
+        pnp_id[1..3].copy_from_slice(&vendor_id.to_le_bytes());
+        pnp_id[3..5].copy_from_slice(&product_id.to_le_bytes());
+        pnp_id[5..7].copy_from_slice(&product_version.to_le_bytes());
+
+        self.pnp_id = Some(pnp_id);
+        self
+    }
+
+    /// Fills the Model Number, Firmware Revision and Software Revision strings from the application
+    /// descriptor: the project name, the application version and the ESP-IDF version.
+    ///
+    /// The strings that are already set are left unchanged.
+    #[cfg(target_os = "espidf")]
+    pub fn app_descriptor(&mut self) -> &mut Self {
+        #[cfg(esp_idf_version_major = "4")]
+        let descriptor = unsafe { esp_ota_get_app_description() };
+
+        #[cfg(not(esp_idf_version_major = "4"))]
+        let descriptor = unsafe { esp_app_get_description() };
+
+        let Some(descriptor) = (unsafe { descriptor.as_ref() }) else {
+            warn!("Cannot read the application descriptor.");
+            return self;
+        };
+
+        self.model_number
+            .get_or_insert_with(|| field_to_string(&descriptor.project_name));
+        self.firmware_revision
+            .get_or_insert_with(|| field_to_string(&descriptor.version));
+        self.software_revision
+            .get_or_insert_with(|| field_to_string(&descriptor.idf_ver));
+
+        self
+    }
+
+    /// Returns the built Device Information [`Service`] behind an `Arc` and an `RwLock`.
+    #[cfg(target_os = "espidf")]
+    #[must_use]
+    pub fn build(&self) -> Arc<RwLock<Service>> {
+        let mut service = Service::new(BleUuid::from_uuid16(0x180A));
+        service.name("Device Information").primary();
+
+        let strings = [
+            (0x2A29, "Manufacturer Name String", &self.manufacturer_name),
+            (0x2A24, "Model Number String", &self.model_number),
+            (0x2A25, "Serial Number String", &self.serial_number),
+            (0x2A27, "Hardware Revision String", &self.hardware_revision),
+            (0x2A26, "Firmware Revision String", &self.firmware_revision),
+            (0x2A28, "Software Revision String", &self.software_revision),
+        ];
+
+        for (uuid, name, value) in strings {
+            match value {
+                // The stack needs at least one byte for automatic responses.
+                Some(value) if value.is_empty() => warn!("{} is empty. Ignoring it.", name),
+                Some(value) => {
+                    service.characteristic(&Self::characteristic(uuid, name, value.as_bytes()));
+                }
+                None => {}
+            }
+        }
+
+        if let Some(system_id) = &self.system_id {
+            service.characteristic(&Self::characteristic(0x2A23, "System ID", system_id));
+        }
+
+        if let Some(pnp_id) = &self.pnp_id {
+            service.characteristic(&Self::characteristic(0x2A50, "PnP ID", pnp_id));
+        }
+
+        service.build()
+    }
+
+    #[cfg(target_os = "espidf")]
+    fn characteristic(uuid: u16, name: &str, value: &[u8]) -> Arc<RwLock<Characteristic>> {
+        Characteristic::new(BleUuid::from_uuid16(uuid))
+            .name(name)
+            .permissions(AttributePermissions::new().read())
+            .properties(CharacteristicProperties::new().read())
+            .set_value(value.to_vec())
+            .build()
+    }
+}
+
+/// Converts a string field of the application descriptor.
+///
+/// The field is not terminated when the string fills it, so the whole field is used in that case.
+fn field_to_string(field: &[c_char]) -> String {
+    let bytes: Vec<u8> = field.iter().map(|byte| byte.to_ne_bytes()[0]).collect();
+
+    match CStr::from_bytes_until_nul(&bytes) {
+        Ok(string) => string.to_string_lossy().into_owned(),
+        Err(_) => String::from_utf8_lossy(&bytes).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(bytes: &[u8]) -> Vec<c_char> {
+        bytes
+            .iter()
+            .map(|byte| c_char::from_ne_bytes([*byte]))
+            .collect()
+    }
+
+    #[test]
+    fn system_id_layout() {
+        let mut device_information = DeviceInformation::new();
+        device_information.system_id(0x01_0203_0405, 0x06_0708);
+
+        assert_eq!(
+            device_information.system_id,
+            Some([0x05, 0x04, 0x03, 0x02, 0x01, 0x08, 0x07, 0x06])
+        );
+    }
+
+    #[test]
+    fn system_id_from_address_layout() {
+        let mut device_information = DeviceInformation::new();
+        device_information.system_id_from_address([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]);
+
+        assert_eq!(
+            device_information.system_id,
+            Some([0xFF, 0xEE, 0xDD, 0xFE, 0xFF, 0xCC, 0xBB, 0xAA])
+        );
+    }
+
+    #[test]
+    fn invalid_system_id() {
+        let mut device_information = DeviceInformation::new();
+        device_information.system_id(1 << 40, 0);
+        device_information.system_id(0, 1 << 24);
+
+        assert_eq!(device_information.system_id, None);
+    }
+
+    #[test]
+    fn pnp_id_layout() {
+        let mut device_information = DeviceInformation::new();
+        device_information.pnp_id(VendorIdSource::UsbImplementersForum, 0x1234, 0x5678, 0x9ABC);
+
+        assert_eq!(
+            device_information.pnp_id,
+            Some([0x02, 0x34, 0x12, 0x78, 0x56, 0xBC, 0x9A])
+        );
+    }
+
+    #[test]
+    fn terminated_field() {
+        assert_eq!(field_to_string(&field(b"app\0\0\0")), "app");
+        assert_eq!(field_to_string(&field(b"\0garbage")), "");
+    }
+
+    #[test]
+    fn unterminated_field() {
+        assert_eq!(field_to_string(&field(b"v5.1.2")), "v5.1.2");
+    }
+
+    #[test]
+    fn invalid_field() {
+        assert_eq!(field_to_string(&field(b"a\xFFb\0")), "a\u{FFFD}b");
+    }
+}
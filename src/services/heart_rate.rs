@@ -0,0 +1,408 @@
+// The encoding of the values does not depend on ESP-IDF, so that it can be tested on the host.
+
+#[cfg(target_os = "espidf")]
+use std::sync::{Arc, RwLock};
+
+#[cfg(target_os = "espidf")]
+use esp_idf_sys::{esp_gatt_status_t, EspError};
+#[cfg(target_os = "espidf")]
+use log::{debug, info};
+
+#[cfg(target_os = "espidf")]
+use crate::{
+    gatt_server::{Characteristic, Service, GLOBAL_GATT_SERVER},
+    utilities::{AttributePermissions, BleUuid, CharacteristicProperties},
+};
+
+/// The error returned when a client writes an unsupported value to the Heart Rate Control Point.
+#[cfg(target_os = "espidf")]
+const CONTROL_POINT_NOT_SUPPORTED: esp_gatt_status_t = 0x80;
+
+/// The Heart Rate Control Point value that resets the energy expended.
+#[cfg(target_os = "espidf")]
+const RESET_ENERGY_EXPENDED: u8 = 0x01;
+
+/// The size of the ATT header of a notification.
+#[cfg(target_os = "espidf")]
+const NOTIFICATION_HEADER_LENGTH: u16 = 3;
+
+/// The location of a heart rate sensor on the body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodySensorLocation {
+    /// A location not listed here.
+    Other = 0x00,
+    /// The chest.
+    Chest = 0x01,
+    /// The wrist.
+    Wrist = 0x02,
+    /// A finger.
+    Finger = 0x03,
+    /// A hand.
+    Hand = 0x04,
+    /// An ear lobe.
+    EarLobe = 0x05,
+    /// A foot.
+    Foot = 0x06,
+}
+
+/// A measurement of the Heart Rate Measurement characteristic (`0x2A37`).
+///
+/// The flags of the encoded value are derived from the fields that are set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeartRateMeasurement {
+    /// The heart rate, in beats per minute.
+    pub heart_rate: u16,
+    /// Whether the sensor detects skin contact, or `None` if the sensor cannot detect it.
+    pub sensor_contact: Option<bool>,
+    /// The energy expended since the last reset, in kilojoules.
+    ///
+    /// It should be included at least every ten measurements, and only if the
+    /// Heart Rate Control Point is enabled with [`HeartRate::energy_expended`].
+    pub energy_expended: Option<u16>,
+    /// The intervals between consecutive R waves, from the oldest to the newest, in units of 1/1024 s.
+    pub rr_intervals: Vec<u16>,
+}
+
+impl HeartRateMeasurement {
+    const UINT16_FORMAT: u8 = 0b0000_0001;
+    const SENSOR_CONTACT_SUPPORTED: u8 = 0b0000_0100;
+    const SENSOR_CONTACT_DETECTED: u8 = 0b0000_0010;
+    const ENERGY_EXPENDED_PRESENT: u8 = 0b0000_1000;
+    const RR_INTERVALS_PRESENT: u8 = 0b0001_0000;
+
+    /// Encodes the measurement into values of at most `max_length` bytes.
+    ///
+    /// The RR-intervals that do not fit in a single value are split across several values,
+    /// each carrying the heart rate and the sensor contact status.
+    /// The energy expended is only carried by the first value.
+    #[must_use]
+    pub fn encode(&self, max_length: usize) -> Vec<Vec<u8>> {
+        let mut flags = match self.sensor_contact {
+            None => 0,
+            Some(false) => Self::SENSOR_CONTACT_SUPPORTED,
+            Some(true) => Self::SENSOR_CONTACT_SUPPORTED | Self::SENSOR_CONTACT_DETECTED,
+        };
+
+        let heart_rate = if let Ok(heart_rate) = u8::try_from(self.heart_rate) {
+            vec![heart_rate]
+        } else {
+            flags |= Self::UINT16_FORMAT;
+            self.heart_rate.to_le_bytes().to_vec()
+        };
+
+        let mut values = Vec::new();
+        let mut energy_expended = self.energy_expended;
+        let mut rr_intervals = self.rr_intervals.as_slice();
+
+        loop {
+            let mut value = vec![flags];
+            value.extend_from_slice(&heart_rate);
+
+            if let Some(energy_expended) = energy_expended.take() {
+                value[0] |= Self::ENERGY_EXPENDED_PRESENT;
+                value.extend_from_slice(&energy_expended.to_le_bytes());
+            }
+
+            // Send at least one interval per value, so that all of them are sent eventually.
+            let count = (max_length.saturating_sub(value.len()) / 2)
+                .max(1)
+                .min(rr_intervals.len());
+            let (sent, remaining) = rr_intervals.split_at(count);
+
+            if !sent.is_empty() {
+                value[0] |= Self::RR_INTERVALS_PRESENT;
+                value.extend(sent.iter().flat_map(|interval| interval.to_le_bytes()));
+            }
+
+            values.push(value);
+            rr_intervals = remaining;
+
+            if rr_intervals.is_empty() {
+                break;
+            }
+        }
+
+        values
+    }
+}
+
+/// The Heart Rate service, with the `0x180D` UUID.
+///
+/// The service exposes the Heart Rate Measurement characteristic, which clients can subscribe to.
+/// The Body Sensor Location and Heart Rate Control Point characteristics are optional.
+///
+/// Measurements are sent with [`HeartRate::notify`], split according to the MTU of each connection.
+///
+/// # Example
+///
+/// ```ignore
+/// let heart_rate = HeartRate::new()
+///     .body_sensor_location(BodySensorLocation::Wrist)
+///     .energy_expended(|| ENERGY_EXPENDED.store(0, Ordering::Relaxed))
+///     .clone();
+///
+/// let profile = Profile::new(0x0001)
+///     .service(&heart_rate.build())
+///     .build();
+///
+/// // Later, from the application.
+/// heart_rate.notify(&HeartRateMeasurement {
+///     heart_rate: 72,
+///     sensor_contact: Some(true),
+///     energy_expended: None,
+///     rr_intervals: vec![850, 842],
+/// })?;
+/// ```
+#[cfg(target_os = "espidf")]
+#[derive(Debug, Clone)]
+pub struct HeartRate {
+    measurement: Arc<RwLock<Characteristic>>,
+    body_sensor_location: Option<Arc<RwLock<Characteristic>>>,
+    control_point: Option<Arc<RwLock<Characteristic>>>,
+}
+
+#[cfg(target_os = "espidf")]
+impl HeartRate {
+    /// Creates a new [`HeartRate`] service, with only the Heart Rate Measurement characteristic.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            measurement: Characteristic::new(BleUuid::from_uuid16(0x2A37))
+                .name("Heart Rate Measurement")
+                .permissions(AttributePermissions::new())
+                .properties(CharacteristicProperties::new().notify())
+                // Flags and heart rate: the value is only sent through notifications.
+                .set_value(vec![0, 0])
+                .build(),
+            body_sensor_location: None,
+            control_point: None,
+        }
+    }
+
+    /// Adds the Body Sensor Location characteristic (`0x2A38`), with the given location.
+    pub fn body_sensor_location(&mut self, location: BodySensorLocation) -> &mut Self {
+        self.body_sensor_location = Some(
+            Characteristic::new(BleUuid::from_uuid16(0x2A38))
+                .name("Body Sensor Location")
+                .permissions(AttributePermissions::new().read())
+                .properties(CharacteristicProperties::new().read())
+                .set_value(vec![location as u8])
+                .build(),
+        );
+        self
+    }
+
+    /// Adds the Heart Rate Control Point characteristic (`0x2A39`), which lets clients reset the energy expended.
+    ///
+    /// The callback is called when a client asks for the reset.
+    /// Other values are rejected with the "Control Point value not supported" error (`0x80`).
+    ///
+    /// # Notes
+    ///
+    /// The callback will be called from the Bluetooth stack's context, so it must not block.
+    pub fn energy_expended(&mut self, on_reset: impl Fn() + Send + Sync + 'static) -> &mut Self {
+        self.control_point = Some(
+            Characteristic::new(BleUuid::from_uuid16(0x2A39))
+                .name("Heart Rate Control Point")
+                .permissions(AttributePermissions::new().write())
+                .properties(CharacteristicProperties::new().write())
+                .set_value(vec![0])
+                .validate_writes(|value| {
+                    if value == [RESET_ENERGY_EXPENDED] {
+                        Ok(())
+                    } else {
+                        Err(CONTROL_POINT_NOT_SUPPORTED)
+                    }
+                })
+                .on_write(move |_, _| {
+                    info!("Resetting the energy expended.");
+                    on_reset();
+                })
+                .build(),
+        );
+        self
+    }
+
+    /// Returns the built Heart Rate [`Service`] behind an `Arc` and an `RwLock`.
+    #[must_use]
+    pub fn build(&self) -> Arc<RwLock<Service>> {
+        let mut service = Service::new(BleUuid::from_uuid16(0x180D));
+        service
+            .name("Heart Rate")
+            .primary()
+            .characteristic(&self.measurement);
+
+        if let Some(body_sensor_location) = &self.body_sensor_location {
+            service.characteristic(body_sensor_location);
+        }
+
+        if let Some(control_point) = &self.control_point {
+            service.characteristic(control_point);
+        }
+
+        service.build()
+    }
+
+    /// Notifies a measurement to the subscribed clients.
+    ///
+    /// The RR-intervals that do not fit in a notification, given the MTU of the connection,
+    /// are sent in the following ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ESP_ERR_INVALID_STATE` error if the service is not registered, or the error of
+    /// the stack if a notification cannot be sent. In that case, the RR-intervals that were not
+    /// sent yet to that client are dropped, and the other clients are still notified.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the global GATT server's lock or a characteristic's lock is poisoned.
+    ///
+    /// # Notes
+    ///
+    /// This must not be called from a callback, or while holding the lock of the [`GLOBAL_GATT_SERVER`].
+    pub fn notify(&self, measurement: &HeartRateMeasurement) -> Result<(), EspError> {
+        debug!("Notifying heart rate measurement {:?}.", measurement);
+
+        GLOBAL_GATT_SERVER
+            .lock()
+            .expect("Cannot lock global GATT server.")
+            .notify_each(&self.measurement, |connection| {
+                measurement.encode(usize::from(
+                    connection.mtu().saturating_sub(NOTIFICATION_HEADER_LENGTH),
+                ))
+            })
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl Default for HeartRate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The maximum length of a notification with the default MTU of 23 bytes.
+    const DEFAULT_MAX_LENGTH: usize = 20;
+
+    fn measurement(heart_rate: u16) -> HeartRateMeasurement {
+        HeartRateMeasurement {
+            heart_rate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn heart_rate_switches_to_uint16_above_255() {
+        assert_eq!(
+            measurement(255).encode(DEFAULT_MAX_LENGTH),
+            [vec![0x00, 0xFF]]
+        );
+        assert_eq!(
+            measurement(256).encode(DEFAULT_MAX_LENGTH),
+            [vec![0x01, 0x00, 0x01]]
+        );
+    }
+
+    #[test]
+    fn sensor_contact_flags() {
+        let flags = |sensor_contact| {
+            HeartRateMeasurement {
+                heart_rate: 72,
+                sensor_contact,
+                ..Default::default()
+            }
+            .encode(DEFAULT_MAX_LENGTH)[0][0]
+        };
+
+        assert_eq!(flags(None), 0x00);
+        assert_eq!(flags(Some(false)), 0x04);
+        assert_eq!(flags(Some(true)), 0x06);
+    }
+
+    #[test]
+    fn energy_expended_only_in_first_value() {
+        let values = HeartRateMeasurement {
+            heart_rate: 72,
+            sensor_contact: None,
+            energy_expended: Some(0x1234),
+            rr_intervals: vec![0x0350; 10],
+        }
+        .encode(DEFAULT_MAX_LENGTH);
+
+        // Flags, heart rate, energy expended, and 8 intervals in the first value.
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0][..4], [0x18, 72, 0x34, 0x12]);
+        assert_eq!(values[0].len(), 4 + 8 * 2);
+        assert_eq!(values[1][..2], [0x10, 72]);
+        assert_eq!(values[1].len(), 2 + 2 * 2);
+    }
+
+    #[test]
+    fn rr_intervals_split_at_default_mtu() {
+        let rr_intervals: Vec<u16> = (1..=20).collect();
+        let values = HeartRateMeasurement {
+            heart_rate: 72,
+            sensor_contact: Some(true),
+            energy_expended: None,
+            rr_intervals: rr_intervals.clone(),
+        }
+        .encode(DEFAULT_MAX_LENGTH);
+
+        // Flags and heart rate, then 9 intervals per value.
+        assert_eq!(values.len(), 3);
+        assert!(values.iter().all(|value| value.len() <= DEFAULT_MAX_LENGTH));
+        assert!(values.iter().all(|value| value[..2] == [0x16, 72]));
+
+        let decoded: Vec<u16> = values
+            .iter()
+            .flat_map(|value| {
+                value[2..]
+                    .chunks_exact(2)
+                    .map(|interval| u16::from_le_bytes([interval[0], interval[1]]))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(decoded, rr_intervals);
+        assert_eq!(values[0].len(), 2 + 9 * 2);
+        assert_eq!(values[2].len(), 2 + 2 * 2);
+    }
+
+    #[test]
+    fn rr_intervals_fit_in_larger_mtu() {
+        let values = HeartRateMeasurement {
+            heart_rate: 300,
+            sensor_contact: None,
+            energy_expended: Some(10),
+            rr_intervals: vec![850; 20],
+        }
+        .encode(usize::from(247_u16 - 3));
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0][0], 0x19);
+        assert_eq!(values[0].len(), 1 + 2 + 2 + 20 * 2);
+    }
+
+    #[test]
+    fn at_least_one_rr_interval_per_value() {
+        let values = HeartRateMeasurement {
+            heart_rate: 72,
+            rr_intervals: vec![1, 2, 3],
+            ..Default::default()
+        }
+        .encode(2);
+
+        assert_eq!(
+            values,
+            [
+                vec![0x10, 72, 1, 0],
+                vec![0x10, 72, 2, 0],
+                vec![0x10, 72, 3, 0]
+            ]
+        );
+    }
+}
@@ -0,0 +1,34 @@
+//! Ready-made standard services.
+//!
+//! Each builder produces a [`Service`] that can be added to a [`Profile`] like any other.
+//!
+//! [`Service`]: crate::gatt_server::Service
+//! [`Profile`]: crate::gatt_server::Profile
+
+// Device Information service.
+#[cfg(any(target_os = "espidf", test))]
+mod device_information;
+#[cfg(any(target_os = "espidf", test))]
+pub use device_information::{DeviceInformation, VendorIdSource};
+
+// Battery service.
+#[cfg(any(target_os = "espidf", test))]
+mod battery;
+#[cfg(target_os = "espidf")]
+pub use battery::Battery;
+#[cfg(any(target_os = "espidf", test))]
+pub use battery::{PowerState, LI_ION_DISCHARGE_CURVE};
+
+// Heart Rate service.
+#[cfg(any(target_os = "espidf", test))]
+mod heart_rate;
+#[cfg(target_os = "espidf")]
+pub use heart_rate::HeartRate;
+#[cfg(any(target_os = "espidf", test))]
+pub use heart_rate::{BodySensorLocation, HeartRateMeasurement};
+
+// Tx Power service.
+#[cfg(target_os = "espidf")]
+mod tx_power;
+#[cfg(target_os = "espidf")]
+pub use tx_power::TxPower;
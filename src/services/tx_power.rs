@@ -0,0 +1,63 @@
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    gatt_server::{Characteristic, Service},
+    utilities::{
+        AttributePermissions, BleUuid, CharacteristicProperties, TxPowerLevel, TxPowerType,
+    },
+};
+
+/// The Tx Power service, with the `0x1804` UUID.
+///
+/// The service exposes the Tx Power Level characteristic, whose value is the advertising power
+/// currently configured with [`GattServer::tx_power`], in dBm.
+///
+/// # Example
+///
+/// ```ignore
+/// let profile = Profile::new(0x0001)
+///     .service(&TxPower::new().build())
+///     .build();
+/// ```
+///
+/// [`GattServer::tx_power`]: crate::gatt_server::GattServer::tx_power
+#[derive(Debug, Clone)]
+pub struct TxPower {
+    level: Arc<RwLock<Characteristic>>,
+}
+
+impl TxPower {
+    /// Creates a new [`TxPower`] service.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn new() -> Self {
+        Self {
+            level: Characteristic::new(BleUuid::from_uuid16(0x2A07))
+                .name("Tx Power Level")
+                .permissions(AttributePermissions::new().read())
+                .properties(CharacteristicProperties::new().read())
+                .on_read(|_| {
+                    let level = TxPowerLevel::current(TxPowerType::Advertising)
+                        .map_or(0, TxPowerLevel::dbm);
+                    vec![level as u8]
+                })
+                .build(),
+        }
+    }
+
+    /// Returns the built Tx Power [`Service`] behind an `Arc` and an `RwLock`.
+    #[must_use]
+    pub fn build(&self) -> Arc<RwLock<Service>> {
+        Service::new(BleUuid::from_uuid16(0x1804))
+            .name("Tx Power")
+            .primary()
+            .characteristic(&self.level)
+            .build()
+    }
+}
+
+impl Default for TxPower {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,126 @@
+use crate::utilities::{Appearance, BleUuid};
+
+/// Represents the payload of an advertisement or scan response packet.
+///
+/// The payload is a sequence of AD structures, each made of a length byte, a type byte and the data.
+/// This is used to describe the payload of an [`ExtendedAdvertisement`].
+///
+/// [`ExtendedAdvertisement`]: crate::gatt_server::ExtendedAdvertisement
+#[derive(Debug, Clone, Default)]
+pub struct AdvertisementData {
+    structures: Vec<(u8, Vec<u8>)>,
+    service_uuids: Vec<BleUuid>,
+}
+
+impl AdvertisementData {
+    /// Creates a new, empty [`AdvertisementData`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the "Flags" AD structure.
+    #[must_use]
+    pub fn flags(self, flags: u8) -> Self {
+        self.raw(0x01, vec![flags])
+    }
+
+    /// Adds the "Complete Local Name" AD structure.
+    #[must_use]
+    pub fn name<S: AsRef<str>>(self, name: S) -> Self {
+        self.raw(0x09, name.as_ref().as_bytes().to_vec())
+    }
+
+    /// Adds the "Shortened Local Name" AD structure.
+    #[must_use]
+    pub fn short_name<S: AsRef<str>>(self, name: S) -> Self {
+        self.raw(0x08, name.as_ref().as_bytes().to_vec())
+    }
+
+    /// Adds the "Tx Power Level" AD structure, in dBm.
+    #[must_use]
+    pub fn tx_power(self, power: i8) -> Self {
+        self.raw(0x0A, power.to_le_bytes().to_vec())
+    }
+
+    /// Adds the "Appearance" AD structure.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn appearance(self, appearance: Appearance) -> Self {
+        let value = i32::from(appearance) as u16;
+        self.raw(0x19, value.to_le_bytes().to_vec())
+    }
+
+    /// Adds a service UUID to the "Complete List of Service UUIDs" AD structures.
+    #[must_use]
+    pub fn service_uuid(mut self, uuid: BleUuid) -> Self {
+        self.service_uuids.push(uuid);
+        self
+    }
+
+    /// Adds a "Service Data" AD structure for the given service.
+    #[must_use]
+    pub fn service_data<T: Into<Vec<u8>>>(self, uuid: BleUuid, data: T) -> Self {
+        let (ad_type, mut value) = match uuid {
+            BleUuid::Uuid16(uuid) => (0x16, uuid.to_le_bytes().to_vec()),
+            BleUuid::Uuid32(uuid) => (0x20, uuid.to_le_bytes().to_vec()),
+            BleUuid::Uuid128(uuid) => (0x21, uuid.to_vec()),
+        };
+
+        value.extend(data.into());
+        self.raw(ad_type, value)
+    }
+
+    /// Adds a "Manufacturer Specific Data" AD structure.
+    #[must_use]
+    pub fn manufacturer_data<T: Into<Vec<u8>>>(self, company_identifier: u16, data: T) -> Self {
+        let mut value = company_identifier.to_le_bytes().to_vec();
+        value.extend(data.into());
+        self.raw(0xFF, value)
+    }
+
+    /// Adds an arbitrary AD structure.
+    #[must_use]
+    pub fn raw<T: Into<Vec<u8>>>(mut self, ad_type: u8, data: T) -> Self {
+        self.structures.push((ad_type, data.into()));
+        self
+    }
+
+    /// Returns the encoded payload.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut uuids_16 = Vec::new();
+        let mut uuids_32 = Vec::new();
+        let mut uuids_128 = Vec::new();
+
+        for uuid in &self.service_uuids {
+            match uuid {
+                BleUuid::Uuid16(uuid) => uuids_16.extend(uuid.to_le_bytes()),
+                BleUuid::Uuid32(uuid) => uuids_32.extend(uuid.to_le_bytes()),
+                BleUuid::Uuid128(uuid) => uuids_128.extend(uuid),
+            }
+        }
+
+        let uuid_structures = [(0x03, uuids_16), (0x05, uuids_32), (0x07, uuids_128)];
+
+        let mut result = Vec::new();
+        for (ad_type, data) in self.structures.iter().cloned().chain(
+            uuid_structures
+                .into_iter()
+                .filter(|(_, data)| !data.is_empty()),
+        ) {
+            result.push(data.len() as u8 + 1);
+            result.push(ad_type);
+            result.extend(data);
+        }
+
+        result
+    }
+}
+
+impl From<AdvertisementData> for Vec<u8> {
+    fn from(data: AdvertisementData) -> Self {
+        data.to_bytes()
+    }
+}
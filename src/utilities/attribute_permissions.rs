@@ -1,14 +1,51 @@
 use esp_idf_sys::*;
+use log::warn;
+
+use crate::utilities::Connection;
+
+/// Represents the security required by the stack to access an attribute.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum SecurityLevel {
+    /// No security is required.
+    #[default]
+    Open,
+    /// The link must be encrypted.
+    Encrypted,
+    /// The link must be encrypted with a key obtained with MITM protection.
+    Authenticated,
+}
 
 /// Represents an attribute's access permissions.
 ///
 /// This struct is used to set the permissions of a [`Characteristic`] or a [`Descriptor`].
 /// It can represent read and write permissions, and encryption requirements.
+///
+/// Read and write requirements are independent: for example, an attribute can be freely readable,
+/// but only writable over an authenticated link.
+///
+/// # Notes
+///
+/// The stack enforces a single minimum key size per attribute.
+/// When different key sizes are set for reading and writing, the larger one applies to both.
+///
+/// The stack has no notion of LE Secure Connections-only attributes. These requirements are checked
+/// by the library for every request, so the attribute is always answered by the application.
+///
+/// [`Characteristic`]: crate::gatt_server::Characteristic
+/// [`Descriptor`]: crate::gatt_server::Descriptor
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct AttributePermissions {
     pub(crate) read_access: bool,
     pub(crate) write_access: bool,
-    pub(crate) encryption_required: bool,
+    read_security: SecurityLevel,
+    write_security: SecurityLevel,
+    pub(crate) signed_writes: bool,
+    authenticated_signed_writes: bool,
+    read_key_size: Option<u8>,
+    write_key_size: Option<u8>,
+    read_secure_connections_only: bool,
+    write_secure_connections_only: bool,
 }
 
 impl AttributePermissions {
@@ -32,31 +69,211 @@ impl AttributePermissions {
         self
     }
 
-    /// Sets the encryption requirement of the [`AttributePermissions`].
+    /// Sets the encryption requirement of the [`AttributePermissions`], for both reading and writing.
     #[must_use]
     pub const fn encrypted(mut self) -> Self {
-        self.encryption_required = true;
+        self.read_security = SecurityLevel::at_least_encrypted(self.read_security);
+        self.write_security = SecurityLevel::at_least_encrypted(self.write_security);
+        self
+    }
+
+    /// Allows reading over an encrypted link only.
+    #[must_use]
+    pub const fn read_encrypted(mut self) -> Self {
+        self.read_access = true;
+        self.read_security = SecurityLevel::at_least_encrypted(self.read_security);
+        self
+    }
+
+    /// Allows writing over an encrypted link only.
+    #[must_use]
+    pub const fn write_encrypted(mut self) -> Self {
+        self.write_access = true;
+        self.write_security = SecurityLevel::at_least_encrypted(self.write_security);
         self
     }
+
+    /// Allows reading over a link encrypted with MITM protection only.
+    #[must_use]
+    pub const fn read_authenticated(mut self) -> Self {
+        self.read_access = true;
+        self.read_security = SecurityLevel::Authenticated;
+        self
+    }
+
+    /// Allows writing over a link encrypted with MITM protection only.
+    #[must_use]
+    pub const fn write_authenticated(mut self) -> Self {
+        self.write_access = true;
+        self.write_security = SecurityLevel::Authenticated;
+        self
+    }
+
+    /// Allows signed writes, which can be sent over an unencrypted link by a bonded client.
+    ///
+    /// The [`CharacteristicProperties::authenticated_signed_writes`] property must be set too.
+    ///
+    /// [`CharacteristicProperties::authenticated_signed_writes`]: crate::utilities::CharacteristicProperties::authenticated_signed_writes
+    #[must_use]
+    pub const fn signed_writes(mut self) -> Self {
+        self.signed_writes = true;
+        self
+    }
+
+    /// Allows signed writes, only if the signing key was obtained with MITM protection.
+    ///
+    /// The [`CharacteristicProperties::authenticated_signed_writes`] property must be set too.
+    ///
+    /// [`CharacteristicProperties::authenticated_signed_writes`]: crate::utilities::CharacteristicProperties::authenticated_signed_writes
+    #[must_use]
+    pub const fn authenticated_signed_writes(mut self) -> Self {
+        self.signed_writes = true;
+        self.authenticated_signed_writes = true;
+        self
+    }
+
+    /// Allows reading over a link that uses LE Secure Connections only.
+    #[must_use]
+    pub const fn read_secure_connections_only(mut self) -> Self {
+        self.read_access = true;
+        self.read_security = SecurityLevel::at_least_encrypted(self.read_security);
+        self.read_secure_connections_only = true;
+        self
+    }
+
+    /// Allows writing over a link that uses LE Secure Connections only.
+    #[must_use]
+    pub const fn write_secure_connections_only(mut self) -> Self {
+        self.write_access = true;
+        self.write_security = SecurityLevel::at_least_encrypted(self.write_security);
+        self.write_secure_connections_only = true;
+        self
+    }
+
+    /// Sets the minimum encryption key size required for reading, between 7 and 16 bytes.
+    ///
+    /// This implies that reading requires an encrypted link.
+    #[must_use]
+    pub fn read_key_size(mut self, size: u8) -> Self {
+        self.read_access = true;
+        self.read_security = SecurityLevel::at_least_encrypted(self.read_security);
+        self.read_key_size = Some(size.clamp(7, 16));
+        self
+    }
+
+    /// Sets the minimum encryption key size required for writing, between 7 and 16 bytes.
+    ///
+    /// This implies that writing requires an encrypted link.
+    #[must_use]
+    pub fn write_key_size(mut self, size: u8) -> Self {
+        self.write_access = true;
+        self.write_security = SecurityLevel::at_least_encrypted(self.write_security);
+        self.write_key_size = Some(size.clamp(7, 16));
+        self
+    }
+
+    /// Returns `true` if the attribute accepts any kind of write.
+    pub(crate) const fn writable(&self) -> bool {
+        self.write_access || self.signed_writes
+    }
+
+    /// Returns `true` if some requirements cannot be enforced by the stack, and must be checked by the library.
+    pub(crate) const fn requires_runtime_checks(&self) -> bool {
+        self.read_secure_connections_only || self.write_secure_connections_only
+    }
+
+    /// Checks the requirements that the stack cannot enforce for a read request.
+    pub(crate) fn check_read(
+        &self,
+        connection: Option<&Connection>,
+    ) -> Result<(), esp_gatt_status_t> {
+        Self::check_secure_connections(self.read_secure_connections_only, connection)
+    }
+
+    /// Checks the requirements that the stack cannot enforce for a write request.
+    pub(crate) fn check_write(
+        &self,
+        connection: Option<&Connection>,
+    ) -> Result<(), esp_gatt_status_t> {
+        Self::check_secure_connections(self.write_secure_connections_only, connection)
+    }
+
+    fn check_secure_connections(
+        required: bool,
+        connection: Option<&Connection>,
+    ) -> Result<(), esp_gatt_status_t> {
+        if !required || connection.is_some_and(Connection::uses_secure_connections) {
+            Ok(())
+        } else {
+            Err(esp_gatt_status_t_ESP_GATT_INSUF_AUTHENTICATION)
+        }
+    }
+
+    /// Returns the minimum key size enforced by the stack.
+    fn key_size(&self) -> Option<u8> {
+        let read_key_size = self.read_key_size.filter(|_| self.read_access);
+        let write_key_size = self.write_key_size.filter(|_| self.write_access);
+
+        match (read_key_size, write_key_size) {
+            (Some(read), Some(write)) => {
+                if read != write {
+                    warn!(
+                        "Different key sizes for reading ({}) and writing ({}) are not supported. Using {} bytes for both.",
+                        read,
+                        write,
+                        read.max(write)
+                    );
+                }
+
+                Some(read.max(write))
+            }
+            (size, None) | (None, size) => size,
+        }
+    }
+}
+
+impl SecurityLevel {
+    const fn at_least_encrypted(level: Self) -> Self {
+        match level {
+            Self::Open | Self::Encrypted => Self::Encrypted,
+            Self::Authenticated => Self::Authenticated,
+        }
+    }
 }
 
 impl From<AttributePermissions> for esp_gatt_perm_t {
     #[allow(clippy::cast_possible_truncation)]
     fn from(permissions: AttributePermissions) -> Self {
-        let result = match (
-            permissions.read_access,
-            permissions.write_access,
-            permissions.encryption_required,
-        ) {
-            // TODO: Implement all the supported modes.
-            (false, false, _) => 0,
-            (true, false, false) => ESP_GATT_PERM_READ,
-            (false, true, false) => ESP_GATT_PERM_WRITE,
-            (true, true, false) => ESP_GATT_PERM_READ | ESP_GATT_PERM_WRITE,
-            (true, false, true) => ESP_GATT_PERM_READ_ENCRYPTED,
-            (false, true, true) => ESP_GATT_PERM_WRITE_ENCRYPTED,
-            (true, true, true) => ESP_GATT_PERM_READ_ENCRYPTED | ESP_GATT_PERM_WRITE_ENCRYPTED,
-        };
+        let mut result = 0;
+
+        if permissions.read_access {
+            result |= match permissions.read_security {
+                SecurityLevel::Open => ESP_GATT_PERM_READ,
+                SecurityLevel::Encrypted => ESP_GATT_PERM_READ_ENCRYPTED,
+                SecurityLevel::Authenticated => ESP_GATT_PERM_READ_ENC_MITM,
+            };
+        }
+
+        if permissions.write_access {
+            result |= match permissions.write_security {
+                SecurityLevel::Open => ESP_GATT_PERM_WRITE,
+                SecurityLevel::Encrypted => ESP_GATT_PERM_WRITE_ENCRYPTED,
+                SecurityLevel::Authenticated => ESP_GATT_PERM_WRITE_ENC_MITM,
+            };
+        }
+
+        if permissions.signed_writes {
+            result |= if permissions.authenticated_signed_writes {
+                ESP_GATT_PERM_WRITE_SIGNED_MITM
+            } else {
+                ESP_GATT_PERM_WRITE_SIGNED
+            };
+        }
+
+        // Equivalent to the `ESP_GATT_PERM_ENCRYPT_KEY_SIZE` macro.
+        if let Some(size) = permissions.key_size() {
+            result |= (u32::from(size - 6) & 0xF) << 12;
+        }
 
         result as Self
     }
@@ -16,14 +16,16 @@ use log::warn;
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Copy, Debug, Default)]
 pub struct CharacteristicProperties {
-    broadcast: bool,
+    pub(crate) broadcast: bool,
     pub(crate) read: bool,
     pub(crate) write_without_response: bool,
     pub(crate) write: bool,
     pub(crate) notify: bool,
     pub(crate) indicate: bool,
-    authenticated_signed_writes: bool,
-    extended_properties: bool,
+    pub(crate) authenticated_signed_writes: bool,
+    pub(crate) extended_properties: bool,
+    pub(crate) reliable_write: bool,
+    pub(crate) writable_auxiliaries: bool,
 }
 
 impl CharacteristicProperties {
@@ -34,6 +36,9 @@ impl CharacteristicProperties {
     }
 
     /// Sets the "broadcast" property.
+    ///
+    /// A "Server Characteristic Configuration" descriptor is added when the characteristic is registered.
+    /// Clients use it to have the value broadcast in the advertisement.
     #[must_use]
     pub const fn broadcast(mut self) -> Self {
         self.broadcast = true;
@@ -93,11 +98,33 @@ impl CharacteristicProperties {
     }
 
     /// Sets the "extended properties" property.
+    ///
+    /// An "Extended Properties" descriptor is added when the characteristic is registered.
     #[must_use]
     pub const fn extended_properties(mut self) -> Self {
         self.extended_properties = true;
         self
     }
+
+    /// Sets the "reliable write" extended property.
+    ///
+    /// This also sets the "extended properties" property.
+    #[must_use]
+    pub const fn reliable_write(mut self) -> Self {
+        self.reliable_write = true;
+        self.extended_properties = true;
+        self
+    }
+
+    /// Sets the "writable auxiliaries" extended property.
+    ///
+    /// This also sets the "extended properties" property.
+    #[must_use]
+    pub const fn writable_auxiliaries(mut self) -> Self {
+        self.writable_auxiliaries = true;
+        self.extended_properties = true;
+        self
+    }
 }
 
 impl From<CharacteristicProperties> for esp_gatt_char_prop_t {
@@ -1,14 +1,381 @@
-use esp_idf_sys::{
-    esp_ble_gatts_cb_param_t_gatts_connect_evt_param,
-    esp_ble_gatts_cb_param_t_gatts_disconnect_evt_param,
+use std::{
+    sync::{Condvar, Mutex},
+    time::Duration,
 };
 
+use esp_idf_sys::*;
+
+#[cfg(not(esp_idf_bt_ble_50_features_supported))]
+use crate::gatt_server::not_supported;
+use crate::utilities::Phy;
+
+/// The address of the device with a pending data length request, if any.
+///
+/// The stack reports neither the device address nor the connection handle when the data length
+/// changes, and also reports the changes requested by the remote devices, so that a single request
+/// can be pending at a time.
+pub(crate) static PENDING_DATA_LENGTH_REQUEST: Mutex<Option<[u8; 6]>> = Mutex::new(None);
+
+/// The RSSI readings reported by the stack and not yet collected, with the address of the device.
+pub(crate) static RSSI_READINGS: Mutex<Vec<([u8; 6], Result<i8, esp_bt_status_t>)>> =
+    Mutex::new(Vec::new());
+
+/// Notified every time an RSSI reading is added to [`RSSI_READINGS`].
+pub(crate) static RSSI_READING_AVAILABLE: Condvar = Condvar::new();
+
+/// The payload length of a link layer packet without Data Length Extension.
+const DEFAULT_DATA_LENGTH: u16 = 27;
+
+/// The ATT MTU of a connection before the client negotiates a larger one.
+const DEFAULT_MTU: u16 = 23;
+
+/// Represents the parameters of a connection, as negotiated with the central.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConnectionParameters {
+    interval: Duration,
+    latency: u16,
+    supervision_timeout: Duration,
+}
+
+impl ConnectionParameters {
+    /// Creates a new [`ConnectionParameters`] from the raw values used by the stack.
+    ///
+    /// The interval is expressed in units of 1.25 ms, the supervision timeout in units of 10 ms.
+    pub(crate) fn from_raw(interval: u16, latency: u16, supervision_timeout: u16) -> Self {
+        Self {
+            interval: Duration::from_micros(u64::from(interval) * 1250),
+            latency,
+            supervision_timeout: Duration::from_millis(u64::from(supervision_timeout) * 10),
+        }
+    }
+
+    /// Returns the connection interval.
+    #[must_use]
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the number of connection events that the peripheral can skip.
+    #[must_use]
+    pub const fn latency(&self) -> u16 {
+        self.latency
+    }
+
+    /// Returns the supervision timeout.
+    #[must_use]
+    pub const fn supervision_timeout(&self) -> Duration {
+        self.supervision_timeout
+    }
+}
+
+/// Represents the security level of a link, from the weakest to the strongest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LinkSecurity {
+    /// The link is not encrypted.
+    Unencrypted,
+    /// The link is encrypted with keys obtained without MITM protection.
+    Encrypted,
+    /// The link is encrypted with keys obtained with MITM protection.
+    Authenticated,
+    /// The link is encrypted with keys obtained with MITM protection and LE Secure Connections.
+    AuthenticatedSecureConnections,
+}
+
+/// Represents a connection with a GATT client.
+///
+/// The active connections can be retrieved with [`GattServer::connections`].
+///
+/// [`GattServer::connections`]: crate::gatt_server::GattServer::connections
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct Connection {
+pub struct Connection {
     pub(crate) id: u16,
     #[cfg(esp_idf_version_major = "4")]
     pub(crate) is_slave: bool,
     pub(crate) remote_bda: [u8; 6],
+    pub(crate) identity_bda: Option<[u8; 6]>,
+    pub(crate) parameters: Option<ConnectionParameters>,
+    pub(crate) tx_phy: Phy,
+    pub(crate) rx_phy: Phy,
+    pub(crate) max_tx_octets: u16,
+    pub(crate) max_rx_octets: u16,
+    pub(crate) mtu: u16,
+    pub(crate) authentication_mode: Option<esp_ble_auth_req_t>,
+}
+
+impl Connection {
+    /// Returns the connection identifier used by the stack.
+    #[must_use]
+    pub const fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Returns the address of the remote device.
+    #[must_use]
+    pub const fn remote_address(&self) -> [u8; 6] {
+        self.remote_bda
+    }
+
+    /// Returns the identity address of the remote device.
+    ///
+    /// This is the address distributed by the remote device while pairing, under which it is bonded.
+    /// It is the same as [`Connection::remote_address`], unless the remote device connected
+    /// with a resolvable private address and distributed its identity.
+    #[must_use]
+    pub fn identity_address(&self) -> [u8; 6] {
+        self.identity_bda.unwrap_or(self.remote_bda)
+    }
+
+    /// Returns `true` if the address is the connection or the identity address of the remote device.
+    pub(crate) fn has_address(&self, address: [u8; 6]) -> bool {
+        self.remote_bda == address || self.identity_bda == Some(address)
+    }
+
+    /// Returns the current connection parameters, if known.
+    #[must_use]
+    pub const fn parameters(&self) -> Option<ConnectionParameters> {
+        self.parameters
+    }
+
+    /// Asks the central to update the connection parameters.
+    ///
+    /// The interval must be between 7.5 ms and 4 s, the latency must be lower than 500 events,
+    /// and the supervision timeout must be between 100 ms and 32 s, and longer than `(1 + latency) * max * 2`.
+    ///
+    /// The central may accept, modify or reject the request.
+    /// The outcome is reported to the callback set with [`GattServer::on_connection_parameters_change`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ESP_ERR_INVALID_ARG` error if the parameters are out of range,
+    /// or an error if the Bluetooth stack cannot send the request.
+    ///
+    /// [`GattServer::on_connection_parameters_change`]: crate::gatt_server::GattServer::on_connection_parameters_change
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn request_parameters(
+        &self,
+        min_interval: Duration,
+        max_interval: Duration,
+        latency: u16,
+        supervision_timeout: Duration,
+    ) -> Result<(), EspError> {
+        let interval_range = Duration::from_micros(7500)..=Duration::from_secs(4);
+        let timeout_range = Duration::from_millis(100)..=Duration::from_secs(32);
+
+        if min_interval > max_interval
+            || !interval_range.contains(&min_interval)
+            || !interval_range.contains(&max_interval)
+            || latency > 499
+            || !timeout_range.contains(&supervision_timeout)
+            || supervision_timeout <= max_interval * (2 * (1 + u32::from(latency)))
+        {
+            return Err(EspError::from(ESP_ERR_INVALID_ARG as esp_err_t).unwrap());
+        }
+
+        let mut parameters = esp_ble_conn_update_params_t {
+            bda: self.remote_bda,
+            min_int: (min_interval.as_micros() / 1250) as u16,
+            max_int: (max_interval.as_micros() / 1250) as u16,
+            latency,
+            timeout: (supervision_timeout.as_millis() / 10) as u16,
+        };
+
+        unsafe { esp!(esp_ble_gap_update_conn_params(&mut parameters)) }
+    }
+
+    /// Returns the current security level of the link.
+    #[must_use]
+    pub fn security(&self) -> LinkSecurity {
+        let Some(mode) = self.authentication_mode.map(u32::from) else {
+            return LinkSecurity::Unencrypted;
+        };
+
+        match (
+            mode & ESP_LE_AUTH_REQ_MITM != 0,
+            mode & ESP_LE_AUTH_REQ_SC_ONLY != 0,
+        ) {
+            (false, _) => LinkSecurity::Encrypted,
+            (true, false) => LinkSecurity::Authenticated,
+            (true, true) => LinkSecurity::AuthenticatedSecureConnections,
+        }
+    }
+
+    /// Returns `true` if the link has been encrypted with the keys of a bond.
+    #[must_use]
+    pub fn is_bonded(&self) -> bool {
+        self.authentication_mode
+            .is_some_and(|mode| u32::from(mode) & ESP_LE_AUTH_BOND != 0)
+    }
+
+    /// Returns `true` if the link has been encrypted with keys obtained with LE Secure Connections.
+    pub(crate) fn uses_secure_connections(&self) -> bool {
+        self.authentication_mode
+            .is_some_and(|mode| u32::from(mode) & ESP_LE_AUTH_REQ_SC_ONLY != 0)
+    }
+
+    /// Returns the PHY used to transmit to the remote device.
+    #[must_use]
+    pub const fn tx_phy(&self) -> Phy {
+        self.tx_phy
+    }
+
+    /// Returns the PHY used to receive from the remote device.
+    #[must_use]
+    pub const fn rx_phy(&self) -> Phy {
+        self.rx_phy
+    }
+
+    /// Returns the maximum payload length of the link layer packets sent to the remote device, in bytes.
+    #[must_use]
+    pub const fn max_tx_octets(&self) -> u16 {
+        self.max_tx_octets
+    }
+
+    /// Returns the maximum payload length of the link layer packets received from the remote device, in bytes.
+    #[must_use]
+    pub const fn max_rx_octets(&self) -> u16 {
+        self.max_rx_octets
+    }
+
+    /// Returns the ATT MTU negotiated with the remote device, in bytes.
+    ///
+    /// Notifications and indications carry at most `mtu - 3` bytes of value.
+    #[must_use]
+    pub const fn mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    /// Sets the preferred PHYs for this connection.
+    ///
+    /// The controller negotiates the PHYs with the remote device, which may not support them.
+    /// The resulting PHYs are available with [`Connection::tx_phy`] and [`Connection::rx_phy`]
+    /// on the connections returned by [`GattServer::connections`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the request,
+    /// or if PHY updates are not supported on this chip.
+    ///
+    /// # Notes
+    ///
+    /// PHY updates are only available on chips that support BLE 5 (ESP32-C3, ESP32-S3),
+    /// with `CONFIG_BT_BLE_50_FEATURES_SUPPORTED` enabled.
+    ///
+    /// [`GattServer::connections`]: crate::gatt_server::GattServer::connections
+    #[cfg(esp_idf_bt_ble_50_features_supported)]
+    pub fn set_preferred_phy(&self, tx: Phy, rx: Phy) -> Result<(), EspError> {
+        let mut address = self.remote_bda;
+
+        unsafe {
+            esp!(esp_ble_gap_set_preferred_phy(
+                address.as_mut_ptr(),
+                0,
+                tx.preference_mask(),
+                rx.preference_mask(),
+                0
+            ))
+        }
+    }
+
+    /// Sets the preferred PHYs for this connection.
+    ///
+    /// # Errors
+    ///
+    /// PHY updates are not supported on this chip, so this always returns an `ESP_ERR_NOT_SUPPORTED` error.
+    #[cfg(not(esp_idf_bt_ble_50_features_supported))]
+    #[allow(clippy::unused_self)]
+    pub fn set_preferred_phy(&self, _tx: Phy, _rx: Phy) -> Result<(), EspError> {
+        Err(not_supported())
+    }
+
+    /// Enables LE Data Length Extension, setting the maximum payload length of the packets sent to the remote device.
+    ///
+    /// The length must be between 27 and 251 bytes.
+    /// The resulting lengths are available with [`Connection::max_tx_octets`] and [`Connection::max_rx_octets`]
+    /// on the connections returned by [`GattServer::connections`].
+    ///
+    /// Only one data length request can be pending at a time, on all the connections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ESP_ERR_INVALID_ARG` error if the length is out of range,
+    /// an `ESP_ERR_INVALID_STATE` error if another data length request is pending,
+    /// or an error if the Bluetooth stack cannot send the request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pending request lock is poisoned.
+    ///
+    /// [`GattServer::connections`]: crate::gatt_server::GattServer::connections
+    pub fn set_data_length(&self, tx_octets: u16) -> Result<(), EspError> {
+        if !(DEFAULT_DATA_LENGTH..=251).contains(&tx_octets) {
+            return Err(EspError::from(ESP_ERR_INVALID_ARG as esp_err_t).unwrap());
+        }
+
+        let mut address = self.remote_bda;
+        let mut pending = PENDING_DATA_LENGTH_REQUEST.lock().unwrap();
+
+        if pending.is_some() {
+            return Err(EspError::from(ESP_ERR_INVALID_STATE as esp_err_t).unwrap());
+        }
+
+        unsafe {
+            esp!(esp_ble_gap_set_pkt_data_len(
+                address.as_mut_ptr(),
+                tx_octets
+            ))?;
+        }
+
+        *pending = Some(self.remote_bda);
+        Ok(())
+    }
+
+    /// Reads the received signal strength of the connection, in dBm, blocking until the controller answers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Bluetooth stack cannot send the request,
+    /// an `ESP_FAIL` error if the controller cannot read the RSSI (for example, after a disconnection),
+    /// or an `ESP_ERR_TIMEOUT` error if no answer is received within the timeout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the RSSI readings lock is poisoned.
+    ///
+    /// # Notes
+    ///
+    /// The answer is delivered by the Bluetooth stack's context, so this must not be called from a callback,
+    /// or while holding the lock of the [`GLOBAL_GATT_SERVER`].
+    ///
+    /// [`GLOBAL_GATT_SERVER`]: crate::gatt_server::GLOBAL_GATT_SERVER
+    pub fn read_rssi(&self, timeout: Duration) -> Result<i8, EspError> {
+        let mut readings = RSSI_READINGS.lock().unwrap();
+        readings.retain(|(address, _)| *address != self.remote_bda);
+
+        let mut address = self.remote_bda;
+        unsafe {
+            esp!(esp_ble_gap_read_rssi(address.as_mut_ptr()))?;
+        }
+
+        let (mut readings, _) = RSSI_READING_AVAILABLE
+            .wait_timeout_while(readings, timeout, |readings| {
+                !readings
+                    .iter()
+                    .any(|(address, _)| *address == self.remote_bda)
+            })
+            .unwrap();
+
+        let Some(index) = readings
+            .iter()
+            .position(|(address, _)| *address == self.remote_bda)
+        else {
+            return Err(EspError::from(ESP_ERR_TIMEOUT as esp_err_t).unwrap());
+        };
+
+        readings
+            .remove(index)
+            .1
+            .map_err(|_| EspError::from(ESP_FAIL).unwrap())
+    }
 }
 
 impl From<esp_ble_gatts_cb_param_t_gatts_connect_evt_param> for Connection {
@@ -18,6 +385,18 @@ impl From<esp_ble_gatts_cb_param_t_gatts_connect_evt_param> for Connection {
             #[cfg(esp_idf_version_major = "4")]
             is_slave: param.link_role == 1,
             remote_bda: param.remote_bda,
+            identity_bda: None,
+            parameters: Some(ConnectionParameters::from_raw(
+                param.conn_params.interval,
+                param.conn_params.latency,
+                param.conn_params.timeout,
+            )),
+            tx_phy: Phy::Le1M,
+            rx_phy: Phy::Le1M,
+            max_tx_octets: DEFAULT_DATA_LENGTH,
+            max_rx_octets: DEFAULT_DATA_LENGTH,
+            mtu: DEFAULT_MTU,
+            authentication_mode: None,
         }
     }
 }
@@ -29,6 +408,14 @@ impl From<esp_ble_gatts_cb_param_t_gatts_disconnect_evt_param> for Connection {
             #[cfg(esp_idf_version_major = "4")]
             is_slave: param.link_role == 1,
             remote_bda: param.remote_bda,
+            identity_bda: None,
+            parameters: None,
+            tx_phy: Phy::Le1M,
+            rx_phy: Phy::Le1M,
+            max_tx_octets: DEFAULT_DATA_LENGTH,
+            max_rx_octets: DEFAULT_DATA_LENGTH,
+            mtu: DEFAULT_MTU,
+            authentication_mode: None,
         }
     }
 }
@@ -0,0 +1,130 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Runs an action with the last value of a burst, once no value has been submitted for a delay.
+pub(crate) struct Debouncer<T> {
+    delay: Duration,
+    action: Arc<dyn Fn(T) + Send + Sync>,
+    /// The value waiting for the action, with the time it was submitted.
+    pending: Arc<Mutex<Option<(T, Instant)>>>,
+}
+
+impl<T: Send + 'static> Debouncer<T> {
+    /// Creates a new [`Debouncer`], that runs the action after the given delay.
+    pub(crate) fn new(delay: Duration, action: impl Fn(T) + Send + Sync + 'static) -> Self {
+        Self {
+            delay,
+            action: Arc::new(action),
+            pending: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Submits a value, replacing the one that is waiting, if any.
+    pub(crate) fn submit(&self, value: T) {
+        let mut pending = self.pending.lock().unwrap();
+        let idle = pending.is_none();
+        *pending = Some((value, Instant::now()));
+
+        // A single thread waits for the values to settle, then runs the action with the last one.
+        if idle {
+            let delay = self.delay;
+            let action = self.action.clone();
+            let pending = self.pending.clone();
+
+            std::thread::spawn(move || loop {
+                let mut pending = pending.lock().unwrap();
+                let elapsed = pending
+                    .as_ref()
+                    .map_or(delay, |(_, submitted_at)| submitted_at.elapsed());
+
+                if elapsed >= delay {
+                    let value = pending.take();
+                    drop(pending);
+
+                    if let Some((value, _)) = value {
+                        action(value);
+                    }
+                    break;
+                }
+
+                drop(pending);
+                std::thread::sleep(delay.saturating_sub(elapsed));
+            });
+        }
+    }
+}
+
+impl<T> Clone for Debouncer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            delay: self.delay,
+            action: self.action.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Debouncer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Debouncer")
+            .field("delay", &self.delay)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(50);
+
+    fn debouncer() -> (Debouncer<u8>, mpsc::Receiver<u8>) {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+
+        let debouncer = Debouncer::new(DELAY, move |value| {
+            sender.lock().unwrap().send(value).unwrap();
+        });
+
+        (debouncer, receiver)
+    }
+
+    #[test]
+    fn burst_runs_once_with_last_value() {
+        let (debouncer, receiver) = debouncer();
+
+        for value in 1..=5 {
+            debouncer.submit(value);
+            std::thread::sleep(DELAY / 5);
+        }
+
+        assert_eq!(receiver.recv_timeout(DELAY * 4), Ok(5));
+        assert!(receiver.recv_timeout(DELAY * 2).is_err());
+    }
+
+    #[test]
+    fn action_waits_for_delay() {
+        let (debouncer, receiver) = debouncer();
+
+        let submitted_at = Instant::now();
+        debouncer.submit(1);
+
+        assert_eq!(receiver.recv_timeout(DELAY * 4), Ok(1));
+        assert!(submitted_at.elapsed() >= DELAY);
+    }
+
+    #[test]
+    fn separate_values_run_separately() {
+        let (debouncer, receiver) = debouncer();
+
+        debouncer.submit(1);
+        assert_eq!(receiver.recv_timeout(DELAY * 4), Ok(1));
+
+        debouncer.submit(2);
+        assert_eq!(receiver.recv_timeout(DELAY * 4), Ok(2));
+    }
+}
@@ -0,0 +1,274 @@
+use crate::utilities::GattValue;
+
+/// The Date Time characteristic structure (0x2A08).
+///
+/// Fields set to zero are unknown, except hours, minutes and seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+}
+
+impl DateTime {
+    /// Creates a new [`DateTime`].
+    ///
+    /// Returns `None` if a field is out of range. The year is between 1582 and 9999,
+    /// the month between 1 and 12 and the day between 1 and 31, or zero if unknown.
+    #[must_use]
+    pub const fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+    ) -> Option<Self> {
+        if (year != 0 && (year < 1582 || year > 9999))
+            || month > 12
+            || day > 31
+            || hours > 23
+            || minutes > 59
+            || seconds > 59
+        {
+            return None;
+        }
+
+        Some(Self {
+            year,
+            month,
+            day,
+            hours,
+            minutes,
+            seconds,
+        })
+    }
+
+    /// Returns the year, or zero if unknown.
+    #[must_use]
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the month, or zero if unknown.
+    #[must_use]
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, or zero if unknown.
+    #[must_use]
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Returns the hours.
+    #[must_use]
+    pub const fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    /// Returns the minutes.
+    #[must_use]
+    pub const fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    /// Returns the seconds.
+    #[must_use]
+    pub const fn seconds(&self) -> u8 {
+        self.seconds
+    }
+}
+
+impl GattValue for DateTime {
+    const LENGTH: Option<usize> = Some(7);
+
+    fn encode(&self) -> Vec<u8> {
+        let [year_low, year_high] = self.year.to_le_bytes();
+
+        vec![
+            year_low,
+            year_high,
+            self.month,
+            self.day,
+            self.hours,
+            self.minutes,
+            self.seconds,
+        ]
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let [year_low, year_high, month, day, hours, minutes, seconds]: [u8; 7] =
+            bytes.try_into().ok()?;
+
+        Self::new(
+            u16::from_le_bytes([year_low, year_high]),
+            month,
+            day,
+            hours,
+            minutes,
+            seconds,
+        )
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hours, self.minutes, self.seconds
+        )
+    }
+}
+
+/// The day of the week, as encoded in the Day of Week characteristic (0x2A09).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DayOfWeek {
+    /// The day of the week is not known.
+    #[default]
+    Unknown = 0,
+    /// Monday.
+    Monday = 1,
+    /// Tuesday.
+    Tuesday = 2,
+    /// Wednesday.
+    Wednesday = 3,
+    /// Thursday.
+    Thursday = 4,
+    /// Friday.
+    Friday = 5,
+    /// Saturday.
+    Saturday = 6,
+    /// Sunday.
+    Sunday = 7,
+}
+
+impl GattValue for DayOfWeek {
+    const LENGTH: Option<usize> = Some(1);
+
+    fn encode(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(Self::Unknown),
+            [1] => Some(Self::Monday),
+            [2] => Some(Self::Tuesday),
+            [3] => Some(Self::Wednesday),
+            [4] => Some(Self::Thursday),
+            [5] => Some(Self::Friday),
+            [6] => Some(Self::Saturday),
+            [7] => Some(Self::Sunday),
+            _ => None,
+        }
+    }
+}
+
+/// The Day Date Time characteristic structure (0x2A0A): a [`DateTime`] followed by a [`DayOfWeek`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DayDateTime {
+    /// The date and time.
+    pub date_time: DateTime,
+    /// The day of the week.
+    pub day_of_week: DayOfWeek,
+}
+
+impl GattValue for DayDateTime {
+    const LENGTH: Option<usize> = Some(8);
+
+    fn encode(&self) -> Vec<u8> {
+        (self.date_time, self.day_of_week).encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let (date_time, day_of_week) = GattValue::decode(bytes)?;
+
+        Some(Self {
+            date_time,
+            day_of_week,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_time_round_trip() {
+        let date_time = DateTime::new(2024, 2, 29, 13, 45, 30).unwrap();
+        let bytes = vec![0xE8, 0x07, 2, 29, 13, 45, 30];
+
+        assert_eq!(date_time.encode(), bytes);
+        assert_eq!(DateTime::decode(&bytes), Some(date_time));
+        assert_eq!(date_time.to_string(), "2024-02-29 13:45:30");
+    }
+
+    #[test]
+    fn date_time_unknown_fields() {
+        let unknown = DateTime::new(0, 0, 0, 0, 0, 0).unwrap();
+
+        assert_eq!(unknown, DateTime::default());
+        assert_eq!(unknown.encode(), vec![0; 7]);
+        assert_eq!(DateTime::decode(&[0; 7]), Some(unknown));
+    }
+
+    #[test]
+    fn date_time_rejects_invalid_fields() {
+        assert_eq!(DateTime::new(1581, 1, 1, 0, 0, 0), None);
+        assert_eq!(DateTime::new(10000, 1, 1, 0, 0, 0), None);
+        assert_eq!(DateTime::new(2024, 13, 1, 0, 0, 0), None);
+        assert_eq!(DateTime::new(2024, 1, 32, 0, 0, 0), None);
+        assert_eq!(DateTime::new(2024, 1, 1, 24, 0, 0), None);
+        assert_eq!(DateTime::new(2024, 1, 1, 0, 60, 0), None);
+        assert_eq!(DateTime::new(2024, 1, 1, 0, 0, 60), None);
+
+        assert_eq!(DateTime::decode(&[0xE8, 0x07, 13, 1, 0, 0, 0]), None);
+        assert_eq!(DateTime::decode(&[0; 6]), None);
+    }
+
+    #[test]
+    fn day_of_week_encoding() {
+        assert_eq!(DayOfWeek::default(), DayOfWeek::Unknown);
+        assert_eq!(DayOfWeek::Unknown.encode(), vec![0]);
+        assert_eq!(DayOfWeek::decode(&[0]), Some(DayOfWeek::Unknown));
+
+        for (byte, day) in [
+            (1, DayOfWeek::Monday),
+            (2, DayOfWeek::Tuesday),
+            (3, DayOfWeek::Wednesday),
+            (4, DayOfWeek::Thursday),
+            (5, DayOfWeek::Friday),
+            (6, DayOfWeek::Saturday),
+            (7, DayOfWeek::Sunday),
+        ] {
+            assert_eq!(day.encode(), vec![byte]);
+            assert_eq!(DayOfWeek::decode(&[byte]), Some(day));
+        }
+
+        assert_eq!(DayOfWeek::decode(&[8]), None);
+        assert_eq!(DayOfWeek::decode(&[]), None);
+    }
+
+    #[test]
+    fn day_date_time_round_trip() {
+        let day_date_time = DayDateTime {
+            date_time: DateTime::new(2024, 2, 29, 13, 45, 30).unwrap(),
+            day_of_week: DayOfWeek::Thursday,
+        };
+
+        let bytes = day_date_time.encode();
+        assert_eq!(bytes, vec![0xE8, 0x07, 2, 29, 13, 45, 30, 4]);
+        assert_eq!(DayDateTime::LENGTH, Some(8));
+        assert_eq!(DayDateTime::decode(&bytes), Some(day_date_time));
+
+        assert_eq!(DayDateTime::decode(&[0; 8]), Some(DayDateTime::default()));
+        assert_eq!(DayDateTime::decode(&[0; 7]), None);
+    }
+}
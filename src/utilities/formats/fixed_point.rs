@@ -0,0 +1,104 @@
+use crate::utilities::GattValue;
+
+/// A number stored as an integer scaled by a decimal exponent: the value is `raw` × 10^`EXPONENT`.
+///
+/// Many characteristics use this representation. For example, the Temperature characteristic (0x2A6E)
+/// is a `FixedPoint<i16, -2>`, in hundredths of a degree Celsius.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedPoint<T, const EXPONENT: i8>(T);
+
+impl<T: Copy, const EXPONENT: i8> FixedPoint<T, EXPONENT> {
+    /// Creates a value from its scaled representation.
+    #[must_use]
+    pub const fn from_raw(raw: T) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the scaled representation of the value.
+    #[must_use]
+    pub const fn raw(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Copy + Into<i64> + TryFrom<i64>, const EXPONENT: i8> FixedPoint<T, EXPONENT> {
+    /// Returns the closest value to an `f64`, or `None` if it does not fit.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn from_f64(value: f64) -> Option<Self> {
+        let raw = (value * 10f64.powi(-i32::from(EXPONENT))).round();
+        if !raw.is_finite() || raw < i64::MIN as f64 || raw > i64::MAX as f64 {
+            return None;
+        }
+
+        T::try_from(raw as i64).ok().map(Self)
+    }
+
+    /// Returns the closest `f64`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_f64(self) -> f64 {
+        self.0.into() as f64 * 10f64.powi(i32::from(EXPONENT))
+    }
+}
+
+impl<T: GattValue, const EXPONENT: i8> GattValue for FixedPoint<T, EXPONENT> {
+    const LENGTH: Option<usize> = T::LENGTH;
+
+    fn encode(&self) -> Vec<u8> {
+        self.0.encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        T::decode(bytes).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Temperature = FixedPoint<i16, -2>;
+
+    #[test]
+    fn scaling() {
+        let temperature = Temperature::from_f64(21.5).unwrap();
+        assert_eq!(temperature.raw(), 2150);
+        assert!((temperature.to_f64() - 21.5).abs() < 1e-9);
+
+        let coarse = FixedPoint::<u8, 1>::from_f64(1234.0).unwrap();
+        assert_eq!(coarse.raw(), 123);
+        assert!((coarse.to_f64() - 1230.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(Temperature::from_f64(21.556).unwrap().raw(), 2156);
+        assert_eq!(Temperature::from_f64(21.554).unwrap().raw(), 2155);
+
+        // Halfway values are rounded away from zero.
+        assert_eq!(Temperature::from_f64(0.125).unwrap().raw(), 13);
+        assert_eq!(Temperature::from_f64(-0.125).unwrap().raw(), -13);
+    }
+
+    #[test]
+    fn out_of_range_values() {
+        assert_eq!(Temperature::from_f64(327.68), None);
+        assert_eq!(Temperature::from_f64(-327.69), None);
+        assert_eq!(FixedPoint::<u8, 0>::from_f64(-1.0), None);
+        assert_eq!(Temperature::from_f64(f64::NAN), None);
+        assert_eq!(Temperature::from_f64(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn encoding() {
+        let temperature = Temperature::from_raw(-2150);
+        assert_eq!(temperature.encode(), (-2150i16).to_le_bytes().to_vec());
+        assert_eq!(
+            Temperature::decode(&temperature.encode()),
+            Some(temperature)
+        );
+        assert_eq!(Temperature::LENGTH, Some(2));
+        assert_eq!(Temperature::decode(&[0]), None);
+    }
+}
@@ -0,0 +1,125 @@
+/// The format of a characteristic value, as used in the Characteristic Presentation Format descriptor.
+///
+/// The values match the Bluetooth SIG assigned numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// A boolean.
+    Boolean = 0x01,
+    /// An unsigned 2-bit integer.
+    Uint2 = 0x02,
+    /// An unsigned 4-bit integer.
+    Uint4 = 0x03,
+    /// An unsigned 8-bit integer.
+    Uint8 = 0x04,
+    /// An unsigned 12-bit integer.
+    Uint12 = 0x05,
+    /// An unsigned 16-bit integer.
+    Uint16 = 0x06,
+    /// An unsigned 24-bit integer.
+    Uint24 = 0x07,
+    /// An unsigned 32-bit integer.
+    Uint32 = 0x08,
+    /// An unsigned 48-bit integer.
+    Uint48 = 0x09,
+    /// An unsigned 64-bit integer.
+    Uint64 = 0x0A,
+    /// An unsigned 128-bit integer.
+    Uint128 = 0x0B,
+    /// A signed 8-bit integer.
+    Sint8 = 0x0C,
+    /// A signed 12-bit integer.
+    Sint12 = 0x0D,
+    /// A signed 16-bit integer.
+    Sint16 = 0x0E,
+    /// A signed 24-bit integer.
+    Sint24 = 0x0F,
+    /// A signed 32-bit integer.
+    Sint32 = 0x10,
+    /// A signed 48-bit integer.
+    Sint48 = 0x11,
+    /// A signed 64-bit integer.
+    Sint64 = 0x12,
+    /// A signed 128-bit integer.
+    Sint128 = 0x13,
+    /// An IEEE-754 32-bit floating point number.
+    Float32 = 0x14,
+    /// An IEEE-754 64-bit floating point number.
+    Float64 = 0x15,
+    /// An IEEE-11073 16-bit floating point number.
+    MedFloat16 = 0x16,
+    /// An IEEE-11073 32-bit floating point number.
+    MedFloat32 = 0x17,
+    /// Two unsigned 16-bit integers.
+    Uint16x2 = 0x18,
+    /// A UTF-8 string.
+    Utf8 = 0x19,
+    /// A UTF-16 string.
+    Utf16 = 0x1A,
+    /// An opaque structure.
+    Struct = 0x1B,
+    /// An IEEE-11073 ASN.1 structure.
+    MedAsn1 = 0x1C,
+}
+
+impl Format {
+    /// Returns the format with the given assigned number, if it exists.
+    #[must_use]
+    pub const fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0x01 => Self::Boolean,
+            0x02 => Self::Uint2,
+            0x03 => Self::Uint4,
+            0x04 => Self::Uint8,
+            0x05 => Self::Uint12,
+            0x06 => Self::Uint16,
+            0x07 => Self::Uint24,
+            0x08 => Self::Uint32,
+            0x09 => Self::Uint48,
+            0x0A => Self::Uint64,
+            0x0B => Self::Uint128,
+            0x0C => Self::Sint8,
+            0x0D => Self::Sint12,
+            0x0E => Self::Sint16,
+            0x0F => Self::Sint24,
+            0x10 => Self::Sint32,
+            0x11 => Self::Sint48,
+            0x12 => Self::Sint64,
+            0x13 => Self::Sint128,
+            0x14 => Self::Float32,
+            0x15 => Self::Float64,
+            0x16 => Self::MedFloat16,
+            0x17 => Self::MedFloat32,
+            0x18 => Self::Uint16x2,
+            0x19 => Self::Utf8,
+            0x1A => Self::Utf16,
+            0x1B => Self::Struct,
+            0x1C => Self::MedAsn1,
+            _ => return None,
+        })
+    }
+
+    /// Returns the length of a value in this format, in bytes, or `None` if it is variable.
+    ///
+    /// Formats shorter than a byte take a whole byte.
+    #[must_use]
+    pub const fn length(self) -> Option<usize> {
+        match self {
+            Self::Boolean | Self::Uint2 | Self::Uint4 | Self::Uint8 | Self::Sint8 => Some(1),
+            Self::Uint12 | Self::Uint16 | Self::Sint12 | Self::Sint16 | Self::MedFloat16 => Some(2),
+            Self::Uint24 | Self::Sint24 => Some(3),
+            Self::Uint32 | Self::Sint32 | Self::Float32 | Self::MedFloat32 | Self::Uint16x2 => {
+                Some(4)
+            }
+            Self::Uint48 | Self::Sint48 => Some(6),
+            Self::Uint64 | Self::Sint64 | Self::Float64 => Some(8),
+            Self::Uint128 | Self::Sint128 => Some(16),
+            Self::Utf8 | Self::Utf16 | Self::Struct | Self::MedAsn1 => None,
+        }
+    }
+}
+
+impl From<Format> for u8 {
+    fn from(format: Format) -> Self {
+        format as Self
+    }
+}
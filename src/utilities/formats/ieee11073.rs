@@ -0,0 +1,300 @@
+use crate::utilities::GattValue;
+
+macro_rules! ieee11073_float {
+    (
+        $(#[$meta:meta])*
+        $name:ident, $raw:ty, $signed:ty, $mantissa_bits:expr, $min_exponent:expr, $max_exponent:expr
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name($raw);
+
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_possible_wrap,
+            clippy::cast_sign_loss
+        )]
+        impl $name {
+            const MANTISSA_MASK: $raw = (1 << $mantissa_bits) - 1;
+            const EXPONENT_BITS: u32 = <$raw>::BITS - $mantissa_bits;
+
+            /// The largest mantissa that is not reserved for a special value.
+            pub const MAX_MANTISSA: $signed = (1 << ($mantissa_bits - 1)) - 3;
+            /// The smallest exponent.
+            pub const MIN_EXPONENT: i8 = $min_exponent;
+            /// The largest exponent.
+            pub const MAX_EXPONENT: i8 = $max_exponent;
+
+            /// Not a number.
+            pub const NAN: Self = Self(Self::MANTISSA_MASK >> 1);
+            /// Not at this resolution: the value cannot be represented with the available precision.
+            pub const NRES: Self = Self((Self::MANTISSA_MASK >> 1) + 1);
+            /// Positive infinity.
+            pub const POSITIVE_INFINITY: Self = Self((Self::MANTISSA_MASK >> 1) - 1);
+            /// Negative infinity.
+            pub const NEGATIVE_INFINITY: Self = Self((Self::MANTISSA_MASK >> 1) + 3);
+            /// The value reserved for future use.
+            const RESERVED: Self = Self((Self::MANTISSA_MASK >> 1) + 2);
+
+            /// Creates a new value equal to `mantissa` × 10^`exponent`.
+            ///
+            /// Returns `None` if the mantissa or the exponent are out of range.
+            #[must_use]
+            pub const fn new(mantissa: $signed, exponent: i8) -> Option<Self> {
+                if mantissa < -Self::MAX_MANTISSA
+                    || mantissa > Self::MAX_MANTISSA
+                    || exponent < Self::MIN_EXPONENT
+                    || exponent > Self::MAX_EXPONENT
+                {
+                    return None;
+                }
+
+                Some(Self(
+                    ((exponent as $raw) << $mantissa_bits) | (mantissa as $raw & Self::MANTISSA_MASK),
+                ))
+            }
+
+            /// Creates a value from its encoded representation.
+            #[must_use]
+            pub const fn from_raw(raw: $raw) -> Self {
+                Self(raw)
+            }
+
+            /// Returns the encoded representation of the value.
+            #[must_use]
+            pub const fn raw(self) -> $raw {
+                self.0
+            }
+
+            /// Returns the mantissa of the value.
+            #[must_use]
+            pub const fn mantissa(self) -> $signed {
+                ((self.0 << Self::EXPONENT_BITS) as $signed) >> Self::EXPONENT_BITS
+            }
+
+            /// Returns the exponent of the value.
+            #[must_use]
+            pub const fn exponent(self) -> i8 {
+                ((self.0 as $signed) >> $mantissa_bits) as i8
+            }
+
+            /// Returns `true` if this is one of the special values: NaN, `NRes`, infinities or reserved.
+            #[must_use]
+            pub const fn is_special(self) -> bool {
+                self.0 == Self::NAN.0
+                    || self.0 == Self::NRES.0
+                    || self.0 == Self::POSITIVE_INFINITY.0
+                    || self.0 == Self::NEGATIVE_INFINITY.0
+                    || self.0 == Self::RESERVED.0
+            }
+
+            /// Returns the closest `f64`.
+            ///
+            /// NaN, `NRes` and the reserved value are converted to `f64::NAN`.
+            #[must_use]
+            pub fn to_f64(self) -> f64 {
+                match self {
+                    Self::POSITIVE_INFINITY => f64::INFINITY,
+                    Self::NEGATIVE_INFINITY => f64::NEG_INFINITY,
+                    _ if self.is_special() => f64::NAN,
+                    _ => f64::from(self.mantissa()) * 10f64.powi(i32::from(self.exponent())),
+                }
+            }
+
+            /// Returns the closest value to an `f64`, with the smallest possible exponent.
+            ///
+            /// Values too large to be represented are converted to infinities.
+            #[must_use]
+            pub fn from_f64(value: f64) -> Self {
+                if value.is_nan() {
+                    return Self::NAN;
+                }
+
+                for exponent in Self::MIN_EXPONENT..=Self::MAX_EXPONENT {
+                    let mantissa = (value * 10f64.powi(-i32::from(exponent))).round();
+
+                    if mantissa.abs() <= f64::from(Self::MAX_MANTISSA) {
+                        if let Some(result) = Self::new(mantissa as $signed, exponent) {
+                            return result;
+                        }
+                    }
+                }
+
+                if value > 0.0 {
+                    Self::POSITIVE_INFINITY
+                } else {
+                    Self::NEGATIVE_INFINITY
+                }
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(value: $name) -> Self {
+                value.to_f64()
+            }
+        }
+
+        impl GattValue for $name {
+            const LENGTH: Option<usize> = Some(std::mem::size_of::<$raw>());
+
+            fn encode(&self) -> Vec<u8> {
+                self.0.to_le_bytes().to_vec()
+            }
+
+            fn decode(bytes: &[u8]) -> Option<Self> {
+                Some(Self(<$raw>::from_le_bytes(bytes.try_into().ok()?)))
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match *self {
+                    Self::NAN => write!(f, "NaN"),
+                    Self::NRES => write!(f, "NRes"),
+                    Self::POSITIVE_INFINITY => write!(f, "+INF"),
+                    Self::NEGATIVE_INFINITY => write!(f, "-INF"),
+                    Self::RESERVED => write!(f, "Reserved"),
+                    _ => write!(f, "{}e{}", self.mantissa(), self.exponent()),
+                }
+            }
+        }
+    };
+}
+
+ieee11073_float!(
+    /// An IEEE-11073 16-bit floating point number, called SFLOAT or medfloat16.
+    ///
+    /// The value is a 12-bit signed mantissa multiplied by 10 raised to a 4-bit signed exponent.
+    SFloat,
+    u16,
+    i16,
+    12,
+    -8,
+    7
+);
+
+ieee11073_float!(
+    /// An IEEE-11073 32-bit floating point number, called FLOAT or medfloat32.
+    ///
+    /// The value is a 24-bit signed mantissa multiplied by 10 raised to an 8-bit signed exponent.
+    Float,
+    u32,
+    i32,
+    24,
+    -128,
+    127
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sfloat_special_values() {
+        let specials = [
+            (SFloat::NAN, 0x07FF, "NaN"),
+            (SFloat::NRES, 0x0800, "NRes"),
+            (SFloat::POSITIVE_INFINITY, 0x07FE, "+INF"),
+            (SFloat::NEGATIVE_INFINITY, 0x0802, "-INF"),
+            (SFloat::RESERVED, 0x0801, "Reserved"),
+        ];
+
+        for (value, raw, name) in specials {
+            assert_eq!(value.raw(), raw);
+            assert!(value.is_special());
+            assert_eq!(value.to_string(), name);
+            assert_eq!(SFloat::decode(&value.encode()), Some(value));
+        }
+
+        assert!(SFloat::NAN.to_f64().is_nan());
+        assert!(SFloat::NRES.to_f64().is_nan());
+        assert!(SFloat::RESERVED.to_f64().is_nan());
+        assert!(SFloat::POSITIVE_INFINITY.to_f64().is_infinite());
+        assert!(SFloat::POSITIVE_INFINITY.to_f64().is_sign_positive());
+        assert!(SFloat::NEGATIVE_INFINITY.to_f64().is_infinite());
+        assert!(SFloat::NEGATIVE_INFINITY.to_f64().is_sign_negative());
+    }
+
+    #[test]
+    fn float_special_values() {
+        let specials = [
+            (Float::NAN, 0x007F_FFFF, "NaN"),
+            (Float::NRES, 0x0080_0000, "NRes"),
+            (Float::POSITIVE_INFINITY, 0x007F_FFFE, "+INF"),
+            (Float::NEGATIVE_INFINITY, 0x0080_0002, "-INF"),
+            (Float::RESERVED, 0x0080_0001, "Reserved"),
+        ];
+
+        for (value, raw, name) in specials {
+            assert_eq!(value.raw(), raw);
+            assert!(value.is_special());
+            assert_eq!(value.to_string(), name);
+            assert_eq!(Float::decode(&value.encode()), Some(value));
+        }
+
+        assert!(Float::NAN.to_f64().is_nan());
+        assert!(Float::POSITIVE_INFINITY.to_f64().is_infinite());
+        assert!(Float::POSITIVE_INFINITY.to_f64().is_sign_positive());
+        assert!(Float::NEGATIVE_INFINITY.to_f64().is_infinite());
+        assert!(Float::NEGATIVE_INFINITY.to_f64().is_sign_negative());
+    }
+
+    #[test]
+    fn sfloat_limits() {
+        assert_eq!(SFloat::MAX_MANTISSA, 2045);
+
+        for (mantissa, exponent) in [(2045, 7), (-2045, -8), (0, 0), (-1, 1)] {
+            let value = SFloat::new(mantissa, exponent).unwrap();
+            assert!(!value.is_special());
+            assert_eq!(value.mantissa(), mantissa);
+            assert_eq!(value.exponent(), exponent);
+        }
+
+        assert_eq!(SFloat::new(2046, 0), None);
+        assert_eq!(SFloat::new(-2046, 0), None);
+        assert_eq!(SFloat::new(0, 8), None);
+        assert_eq!(SFloat::new(0, -9), None);
+    }
+
+    #[test]
+    fn float_limits() {
+        assert_eq!(Float::MAX_MANTISSA, 8_388_605);
+
+        for (mantissa, exponent) in [(8_388_605, 127), (-8_388_605, -128), (-1, -1)] {
+            let value = Float::new(mantissa, exponent).unwrap();
+            assert!(!value.is_special());
+            assert_eq!(value.mantissa(), mantissa);
+            assert_eq!(value.exponent(), exponent);
+        }
+
+        assert_eq!(Float::new(8_388_606, 0), None);
+        assert_eq!(Float::new(-8_388_606, 0), None);
+    }
+
+    #[test]
+    fn encoding_is_little_endian() {
+        let value = SFloat::new(-123, -2).unwrap();
+        assert_eq!(value.raw(), 0xEF85);
+        assert_eq!(value.encode(), vec![0x85, 0xEF]);
+
+        assert_eq!(SFloat::decode(&[0x85]), None);
+        assert_eq!(Float::decode(&[0x85, 0xEF]), None);
+    }
+
+    #[test]
+    fn conversions_from_f64() {
+        let temperature = SFloat::from_f64(36.6);
+        assert_eq!((temperature.mantissa(), temperature.exponent()), (366, -1));
+        assert!((temperature.to_f64() - 36.6).abs() < 1e-9);
+
+        let large = SFloat::from_f64(-123_000.0);
+        assert_eq!((large.mantissa(), large.exponent()), (-1230, 2));
+
+        assert_eq!(SFloat::from_f64(f64::NAN), SFloat::NAN);
+        assert_eq!(SFloat::from_f64(1e300), SFloat::POSITIVE_INFINITY);
+        assert_eq!(SFloat::from_f64(-1e300), SFloat::NEGATIVE_INFINITY);
+
+        let precise = Float::from_f64(98.765);
+        assert_eq!((precise.mantissa(), precise.exponent()), (987_650, -4));
+    }
+}
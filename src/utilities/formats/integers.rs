@@ -0,0 +1,240 @@
+use crate::utilities::GattValue;
+
+macro_rules! unsigned_integer {
+    ($(#[$meta:meta])* $name:ident, $inner:ty, $bytes:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name($inner);
+
+        impl $name {
+            /// The largest value.
+            pub const MAX: Self = Self((1 << ($bytes * 8)) - 1);
+
+            /// Creates a new value, returning `None` if it does not fit.
+            #[must_use]
+            pub const fn new(value: $inner) -> Option<Self> {
+                if value > Self::MAX.0 {
+                    None
+                } else {
+                    Some(Self(value))
+                }
+            }
+
+            /// Returns the value.
+            #[must_use]
+            pub const fn get(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl From<$name> for $inner {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl TryFrom<$inner> for $name {
+            type Error = ();
+
+            fn try_from(value: $inner) -> Result<Self, Self::Error> {
+                Self::new(value).ok_or(())
+            }
+        }
+
+        impl From<$name> for i64 {
+            #[allow(clippy::cast_lossless, clippy::cast_possible_wrap)]
+            fn from(value: $name) -> Self {
+                value.0 as Self
+            }
+        }
+
+        impl TryFrom<i64> for $name {
+            type Error = ();
+
+            fn try_from(value: i64) -> Result<Self, Self::Error> {
+                <$inner>::try_from(value).ok().and_then(Self::new).ok_or(())
+            }
+        }
+
+        impl GattValue for $name {
+            const LENGTH: Option<usize> = Some($bytes);
+
+            fn encode(&self) -> Vec<u8> {
+                self.0.to_le_bytes()[..$bytes].to_vec()
+            }
+
+            fn decode(bytes: &[u8]) -> Option<Self> {
+                if bytes.len() != $bytes {
+                    return None;
+                }
+
+                let mut buf = [0u8; std::mem::size_of::<$inner>()];
+                buf[..$bytes].copy_from_slice(bytes);
+                Some(Self(<$inner>::from_le_bytes(buf)))
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+unsigned_integer!(
+    /// An unsigned 24-bit integer, encoded in three bytes.
+    Uint24,
+    u32,
+    3
+);
+
+unsigned_integer!(
+    /// An unsigned 48-bit integer, encoded in six bytes.
+    Uint48,
+    u64,
+    6
+);
+
+/// A signed 24-bit integer, encoded in three bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sint24(i32);
+
+impl Sint24 {
+    /// The smallest value.
+    pub const MIN: Self = Self(-(1 << 23));
+    /// The largest value.
+    pub const MAX: Self = Self((1 << 23) - 1);
+
+    /// Creates a new value, returning `None` if it does not fit.
+    #[must_use]
+    pub const fn new(value: i32) -> Option<Self> {
+        if value < Self::MIN.0 || value > Self::MAX.0 {
+            None
+        } else {
+            Some(Self(value))
+        }
+    }
+
+    /// Returns the value.
+    #[must_use]
+    pub const fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<Sint24> for i32 {
+    fn from(value: Sint24) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<i32> for Sint24 {
+    type Error = ();
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Self::new(value).ok_or(())
+    }
+}
+
+impl From<Sint24> for i64 {
+    fn from(value: Sint24) -> Self {
+        Self::from(value.0)
+    }
+}
+
+impl TryFrom<i64> for Sint24 {
+    type Error = ();
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        i32::try_from(value).ok().and_then(Self::new).ok_or(())
+    }
+}
+
+impl GattValue for Sint24 {
+    const LENGTH: Option<usize> = Some(3);
+
+    fn encode(&self) -> Vec<u8> {
+        self.0.to_le_bytes()[..3].to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let [low, middle, high]: [u8; 3] = bytes.try_into().ok()?;
+
+        // Extend the sign bit into the most significant byte.
+        Some(Self(i32::from_le_bytes([low, middle, high, 0]) << 8 >> 8))
+    }
+}
+
+impl std::fmt::Display for Sint24 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uint24_round_trip() {
+        let value = Uint24::new(0x0012_3456).unwrap();
+        assert_eq!(value.encode(), vec![0x56, 0x34, 0x12]);
+        assert_eq!(Uint24::decode(&[0x56, 0x34, 0x12]), Some(value));
+
+        assert_eq!(Uint24::MAX.get(), 0x00FF_FFFF);
+        assert_eq!(Uint24::MAX.encode(), vec![0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn uint24_rejects_out_of_range_values() {
+        assert_eq!(Uint24::new(0x0100_0000), None);
+        assert_eq!(Uint24::try_from(-1i64), Err(()));
+        assert_eq!(Uint24::try_from(0x0100_0000i64), Err(()));
+        assert_eq!(i64::from(Uint24::MAX), 0x00FF_FFFF);
+
+        assert_eq!(Uint24::decode(&[0x01, 0x02]), None);
+        assert_eq!(Uint24::decode(&[0x01, 0x02, 0x03, 0x04]), None);
+    }
+
+    #[test]
+    fn uint48_round_trip() {
+        let value = Uint48::new(0x0102_0304_0506).unwrap();
+        assert_eq!(value.encode(), vec![0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        assert_eq!(Uint48::decode(&value.encode()), Some(value));
+
+        assert_eq!(Uint48::MAX.get(), 0xFFFF_FFFF_FFFF);
+        assert_eq!(Uint48::new(1 << 48), None);
+        assert_eq!(Uint48::decode(&[0; 8]), None);
+    }
+
+    #[test]
+    fn sint24_sign_extension() {
+        let cases = [
+            (-1, [0xFF, 0xFF, 0xFF]),
+            (-8_388_608, [0x00, 0x00, 0x80]),
+            (8_388_607, [0xFF, 0xFF, 0x7F]),
+            (-256, [0x00, 0xFF, 0xFF]),
+            (1, [0x01, 0x00, 0x00]),
+        ];
+
+        for (value, bytes) in cases {
+            let value = Sint24::new(value).unwrap();
+            assert_eq!(value.encode(), bytes.to_vec());
+            assert_eq!(Sint24::decode(&bytes), Some(value));
+        }
+
+        assert_eq!(Sint24::decode(&[0x00, 0x00, 0x80]), Some(Sint24::MIN));
+    }
+
+    #[test]
+    fn sint24_rejects_out_of_range_values() {
+        assert_eq!(Sint24::new(8_388_608), None);
+        assert_eq!(Sint24::new(-8_388_609), None);
+        assert_eq!(Sint24::try_from(i64::from(i32::MAX) + 1), Err(()));
+        assert_eq!(i64::from(Sint24::MIN), -8_388_608);
+
+        assert_eq!(Sint24::decode(&[0xFF, 0xFF]), None);
+        assert_eq!(Sint24::decode(&[0xFF, 0xFF, 0xFF, 0xFF]), None);
+    }
+}
@@ -0,0 +1,30 @@
+//! Encoders and decoders for the value formats defined by the Bluetooth SIG.
+//!
+//! Every type implements [`GattValue`], so it can be used with a [`TypedCharacteristic`].
+//!
+//! [`GattValue`]: crate::utilities::GattValue
+//! [`TypedCharacteristic`]: crate::gatt_server::TypedCharacteristic
+
+// IEEE-11073 floating point numbers.
+mod ieee11073;
+pub use ieee11073::{Float, SFloat};
+
+// Integers with uncommon sizes.
+mod integers;
+pub use integers::{Sint24, Uint24, Uint48};
+
+// Decimal fixed point numbers.
+mod fixed_point;
+pub use fixed_point::FixedPoint;
+
+// Date and time structures.
+mod date_time;
+pub use date_time::{DateTime, DayDateTime, DayOfWeek};
+
+// Presentation formats.
+mod format;
+pub use format::Format;
+
+// Units.
+mod unit;
+pub use unit::Unit;
@@ -0,0 +1,148 @@
+#![allow(missing_docs)]
+
+/// A list of standard units, used in the Characteristic Presentation Format descriptor.
+///
+/// This list was copied from the Bluetooth SIG website.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Unitless = 0x2700,
+    LengthMetre = 0x2701,
+    MassKilogram = 0x2702,
+    TimeSecond = 0x2703,
+    ElectricCurrentAmpere = 0x2704,
+    ThermodynamicTemperatureKelvin = 0x2705,
+    AmountOfSubstanceMole = 0x2706,
+    LuminousIntensityCandela = 0x2707,
+    AreaSquareMetres = 0x2710,
+    VolumeCubicMetres = 0x2711,
+    VelocityMetresPerSecond = 0x2712,
+    AccelerationMetresPerSecondSquared = 0x2713,
+    WavenumberReciprocalMetre = 0x2714,
+    DensityKilogramPerCubicMetre = 0x2715,
+    SurfaceDensityKilogramPerSquareMetre = 0x2716,
+    SpecificVolumeCubicMetrePerKilogram = 0x2717,
+    CurrentDensityAmperePerSquareMetre = 0x2718,
+    MagneticFieldStrengthAmperePerMetre = 0x2719,
+    AmountConcentrationMolePerCubicMetre = 0x271A,
+    MassConcentrationKilogramPerCubicMetre = 0x271B,
+    LuminanceCandelaPerSquareMetre = 0x271C,
+    RefractiveIndex = 0x271D,
+    RelativePermeability = 0x271E,
+    PlaneAngleRadian = 0x2720,
+    SolidAngleSteradian = 0x2721,
+    FrequencyHertz = 0x2722,
+    ForceNewton = 0x2723,
+    PressurePascal = 0x2724,
+    EnergyJoule = 0x2725,
+    PowerWatt = 0x2726,
+    ElectricChargeCoulomb = 0x2727,
+    ElectricPotentialDifferenceVolt = 0x2728,
+    CapacitanceFarad = 0x2729,
+    ElectricResistanceOhm = 0x272A,
+    ElectricConductanceSiemens = 0x272B,
+    MagneticFluxWeber = 0x272C,
+    MagneticFluxDensityTesla = 0x272D,
+    InductanceHenry = 0x272E,
+    ThermodynamicTemperatureDegreeCelsius = 0x272F,
+    LuminousFluxLumen = 0x2730,
+    IlluminanceLux = 0x2731,
+    ActivityReferredToARadionuclideBecquerel = 0x2732,
+    AbsorbedDoseGray = 0x2733,
+    DoseEquivalentSievert = 0x2734,
+    CatalyticActivityKatal = 0x2735,
+    DynamicViscosityPascalSecond = 0x2740,
+    MomentOfForceNewtonMetre = 0x2741,
+    SurfaceTensionNewtonPerMetre = 0x2742,
+    AngularVelocityRadianPerSecond = 0x2743,
+    AngularAccelerationRadianPerSecondSquared = 0x2744,
+    HeatFluxDensityWattPerSquareMetre = 0x2745,
+    HeatCapacityJoulePerKelvin = 0x2746,
+    SpecificHeatCapacityJoulePerKilogramKelvin = 0x2747,
+    SpecificEnergyJoulePerKilogram = 0x2748,
+    ThermalConductivityWattPerMetreKelvin = 0x2749,
+    EnergyDensityJoulePerCubicMetre = 0x274A,
+    ElectricFieldStrengthVoltPerMetre = 0x274B,
+    ElectricChargeDensityCoulombPerCubicMetre = 0x274C,
+    SurfaceChargeDensityCoulombPerSquareMetre = 0x274D,
+    ElectricFluxDensityCoulombPerSquareMetre = 0x274E,
+    PermittivityFaradPerMetre = 0x274F,
+    PermeabilityHenryPerMetre = 0x2750,
+    MolarEnergyJoulePerMole = 0x2751,
+    MolarEntropyJoulePerMoleKelvin = 0x2752,
+    ExposureCoulombPerKilogram = 0x2753,
+    AbsorbedDoseRateGrayPerSecond = 0x2754,
+    RadiantIntensityWattPerSteradian = 0x2755,
+    RadianceWattPerSquareMetreSteradian = 0x2756,
+    CatalyticActivityConcentrationKatalPerCubicMetre = 0x2757,
+    TimeMinute = 0x2760,
+    TimeHour = 0x2761,
+    TimeDay = 0x2762,
+    PlaneAngleDegree = 0x2763,
+    PlaneAngleMinute = 0x2764,
+    PlaneAngleSecond = 0x2765,
+    AreaHectare = 0x2766,
+    VolumeLitre = 0x2767,
+    MassTonne = 0x2768,
+    PressureBar = 0x2780,
+    PressureMillimetreOfMercury = 0x2781,
+    LengthAngstrom = 0x2782,
+    LengthNauticalMile = 0x2783,
+    AreaBarn = 0x2784,
+    VelocityKnot = 0x2785,
+    LogarithmicRadioQuantityNeper = 0x2786,
+    LogarithmicRadioQuantityBel = 0x2787,
+    LengthYard = 0x27A0,
+    LengthParsec = 0x27A1,
+    LengthInch = 0x27A2,
+    LengthFoot = 0x27A3,
+    LengthMile = 0x27A4,
+    PressurePoundForcePerSquareInch = 0x27A5,
+    VelocityKilometrePerHour = 0x27A6,
+    VelocityMilePerHour = 0x27A7,
+    AngularVelocityRevolutionPerMinute = 0x27A8,
+    EnergyGramCalorie = 0x27A9,
+    EnergyKilogramCalorie = 0x27AA,
+    EnergyKilowattHour = 0x27AB,
+    ThermodynamicTemperatureDegreeFahrenheit = 0x27AC,
+    Percentage = 0x27AD,
+    PerMille = 0x27AE,
+    PeriodBeatsPerMinute = 0x27AF,
+    ElectricChargeAmpereHours = 0x27B0,
+    MassDensityMilligramPerDecilitre = 0x27B1,
+    MassDensityMillimolePerLitre = 0x27B2,
+    TimeYear = 0x27B3,
+    TimeMonth = 0x27B4,
+    ConcentrationCountPerCubicMetre = 0x27B5,
+    IrradianceWattPerSquareMetre = 0x27B6,
+    MillilitrePerKilogramPerMinute = 0x27B7,
+    MassPound = 0x27B8,
+    MetabolicEquivalent = 0x27B9,
+    StepPerMinute = 0x27BA,
+    StrokePerMinute = 0x27BC,
+    PaceKilometrePerMinute = 0x27BD,
+    LuminousEfficacyLumenPerWatt = 0x27BE,
+    LuminousEnergyLumenHour = 0x27BF,
+    LuminousExposureLuxHour = 0x27C0,
+    MassFlowGramPerSecond = 0x27C1,
+    VolumeFlowLitrePerSecond = 0x27C2,
+    SoundPressureDecibel = 0x27C3,
+    PartsPerMillion = 0x27C4,
+    PartsPerBillion = 0x27C5,
+    MassDensityRateMilligramPerDecilitrePerMinute = 0x27C6,
+    ElectricalApparentEnergyKilovoltAmpereHour = 0x27C7,
+    ElectricalApparentPowerVoltAmpere = 0x27C8,
+}
+
+impl Unit {
+    /// Returns the 16-bit UUID of this unit.
+    #[must_use]
+    pub const fn uuid16(self) -> u16 {
+        self as u16
+    }
+}
+
+impl From<Unit> for u16 {
+    fn from(unit: Unit) -> Self {
+        unit as Self
+    }
+}
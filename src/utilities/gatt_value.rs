@@ -0,0 +1,162 @@
+/// A type that can be stored in the value of a characteristic or descriptor.
+///
+/// Numbers are encoded in little-endian order, as required by the Bluetooth specification.
+/// Strings are encoded in UTF-8, without a terminator.
+/// Arrays and tuples are the concatenation of their elements.
+pub trait GattValue: Sized {
+    /// The length of the encoded value, or `None` if it is variable.
+    const LENGTH: Option<usize>;
+
+    /// Encodes the value into bytes.
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decodes a value from bytes, returning `None` if they are malformed.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_gatt_value_for_numbers {
+    ($($t:ty),*) => {
+        $(
+            impl GattValue for $t {
+                const LENGTH: Option<usize> = Some(std::mem::size_of::<$t>());
+
+                fn encode(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self> {
+                    Some(Self::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_gatt_value_for_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl GattValue for bool {
+    const LENGTH: Option<usize> = Some(1);
+
+    fn encode(&self) -> Vec<u8> {
+        vec![u8::from(*self)]
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl GattValue for String {
+    const LENGTH: Option<usize> = None;
+
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Self::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+impl GattValue for Vec<u8> {
+    const LENGTH: Option<usize> = None;
+
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+/// Arrays are supported for elements with a fixed length.
+impl<T: GattValue, const N: usize> GattValue for [T; N] {
+    const LENGTH: Option<usize> = match T::LENGTH {
+        Some(length) => Some(length * N),
+        None => None,
+    };
+
+    fn encode(&self) -> Vec<u8> {
+        self.iter().flat_map(GattValue::encode).collect()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let length = T::LENGTH?;
+        if bytes.len() != length * N {
+            return None;
+        }
+
+        let elements: Vec<T> = bytes
+            .chunks_exact(length)
+            .map(T::decode)
+            .collect::<Option<_>>()?;
+
+        elements.try_into().ok()
+    }
+}
+
+macro_rules! impl_gatt_value_for_tuples {
+    ($(($($t:ident),+; $last:ident)),*) => {
+        $(
+            /// Tuples are supported when all the elements but the last one have a fixed length.
+            #[allow(non_snake_case, clippy::many_single_char_names)]
+            impl<$($t: GattValue,)+ $last: GattValue> GattValue for ($($t,)+ $last) {
+                const LENGTH: Option<usize> = {
+                    let lengths = [$($t::LENGTH,)+ $last::LENGTH];
+                    let mut total = 0;
+                    let mut i = 0;
+                    let mut fixed = true;
+                    while i < lengths.len() {
+                        match lengths[i] {
+                            Some(length) => total += length,
+                            None => fixed = false,
+                        }
+                        i += 1;
+                    }
+
+                    if fixed {
+                        Some(total)
+                    } else {
+                        None
+                    }
+                };
+
+                fn encode(&self) -> Vec<u8> {
+                    let ($($t,)+ $last) = self;
+                    let mut bytes = Vec::new();
+                    $(bytes.extend($t.encode());)+
+                    bytes.extend($last.encode());
+                    bytes
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self> {
+                    let mut rest = bytes;
+                    $(
+                        let length = $t::LENGTH?;
+                        if rest.len() < length {
+                            return None;
+                        }
+                        let (head, tail) = rest.split_at(length);
+                        let $t = $t::decode(head)?;
+                        rest = tail;
+                    )+
+
+                    Some(($($t,)+ $last::decode(rest)?))
+                }
+            }
+        )*
+    };
+}
+
+impl_gatt_value_for_tuples!(
+    (A; B),
+    (A, B; C),
+    (A, B, C; D),
+    (A, B, C, D; E),
+    (A, B, C, D, E; F)
+);
@@ -0,0 +1,57 @@
+use esp_idf_sys::*;
+use std::time::Duration;
+
+/// Represents the address that the device uses to identify itself over the air.
+///
+/// This is used to configure the [`GattServer`] with the [`GattServer::local_address`] method.
+///
+/// [`GattServer`]: crate::gatt_server::GattServer
+/// [`GattServer::local_address`]: crate::gatt_server::GattServer::local_address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LocalAddress {
+    /// The public address of the device, derived from the factory-programmed MAC address.
+    #[default]
+    Public,
+    /// A random static address, generated on first boot and persisted in NVS.
+    ///
+    /// The same address is used across reboots, until the NVS is erased.
+    RandomStatic,
+    /// A user-supplied random static address.
+    ///
+    /// The two most significant bits of the first byte must be set.
+    CustomRandomStatic([u8; 6]),
+    /// A resolvable private address, generated by the stack from the local IRK.
+    ///
+    /// The address is rotated at the given interval, which is clamped between one second and one hour.
+    /// Bonded peers can resolve it thanks to the identity key exchanged during pairing.
+    ResolvablePrivate(Duration),
+}
+
+impl LocalAddress {
+    /// Returns `true` if the given address is a valid random static address.
+    pub(crate) const fn is_random_static(address: [u8; 6]) -> bool {
+        address[0] & 0b1100_0000 == 0b1100_0000
+    }
+
+    /// Returns the RPA rotation interval in seconds, as expected by the Bluetooth stack.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn rotation_timeout(&self) -> Option<u16> {
+        if let Self::ResolvablePrivate(interval) = self {
+            Some(interval.as_secs().clamp(1, 3600) as u16)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<LocalAddress> for esp_ble_addr_type_t {
+    fn from(address: LocalAddress) -> Self {
+        match address {
+            LocalAddress::Public => esp_ble_addr_type_t_BLE_ADDR_TYPE_PUBLIC,
+            LocalAddress::RandomStatic | LocalAddress::CustomRandomStatic(_) => {
+                esp_ble_addr_type_t_BLE_ADDR_TYPE_RANDOM
+            }
+            LocalAddress::ResolvablePrivate(_) => esp_ble_addr_type_t_BLE_ADDR_TYPE_RPA_PUBLIC,
+        }
+    }
+}
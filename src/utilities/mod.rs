@@ -1,28 +1,96 @@
 //! This module contains useful structs and macros for the crate.
 
 // Leaky box: internally useful for ffi C interfacing.
+#[cfg(target_os = "espidf")]
 pub(crate) mod leaky_box;
 
 // Utilities: private.
+#[cfg(target_os = "espidf")]
 mod attribute_control;
+#[cfg(target_os = "espidf")]
 pub(crate) use attribute_control::AttributeControl;
 
-// Connection: private.
+// Connection: public.
+#[cfg(target_os = "espidf")]
 mod connection;
-pub(crate) use connection::Connection;
+#[cfg(target_os = "espidf")]
+pub use connection::{Connection, ConnectionParameters, LinkSecurity};
+#[cfg(target_os = "espidf")]
+pub(crate) use connection::{
+    PENDING_DATA_LENGTH_REQUEST, RSSI_READINGS, RSSI_READING_AVAILABLE,
+};
 
 // BLE identifiers: public.
+#[cfg(target_os = "espidf")]
 mod ble_uuid;
+#[cfg(target_os = "espidf")]
 pub use ble_uuid::BleUuid;
 
 // Bluetooth device appearance: public.
+#[cfg(target_os = "espidf")]
 mod appearance;
+#[cfg(target_os = "espidf")]
 pub use appearance::Appearance;
 
 // Characteristic properties: public.
+#[cfg(target_os = "espidf")]
 mod characteristic_properties;
+#[cfg(target_os = "espidf")]
 pub use characteristic_properties::CharacteristicProperties;
 
 // Attribute permissions: public.
+#[cfg(target_os = "espidf")]
 mod attribute_permissions;
+#[cfg(target_os = "espidf")]
 pub use attribute_permissions::AttributePermissions;
+
+// Local address configuration: public.
+#[cfg(target_os = "espidf")]
+mod local_address;
+#[cfg(target_os = "espidf")]
+pub use local_address::LocalAddress;
+
+// Physical layers: public.
+#[cfg(target_os = "espidf")]
+mod phy;
+#[cfg(target_os = "espidf")]
+pub use phy::Phy;
+
+// Advertisement payloads: public.
+#[cfg(target_os = "espidf")]
+mod advertisement_data;
+#[cfg(target_os = "espidf")]
+pub use advertisement_data::AdvertisementData;
+
+// Transmit power: public.
+#[cfg(target_os = "espidf")]
+mod tx_power;
+#[cfg(target_os = "espidf")]
+pub use tx_power::{TxPowerLevel, TxPowerType};
+
+// Security Manager configuration: public.
+#[cfg(target_os = "espidf")]
+mod security_config;
+#[cfg(target_os = "espidf")]
+pub use security_config::{IoCapabilities, KeyDistribution, SecurityConfig};
+
+// Persistent storage backends: public, and available on the host for tests.
+mod storage;
+#[cfg(target_os = "espidf")]
+pub use storage::NvsStorage;
+pub use storage::{FileStorage, MemoryStorage, Storage, StorageError};
+#[cfg(target_os = "espidf")]
+pub(crate) use storage::{is_valid_key, MAX_KEY_LENGTH};
+
+// Debounced actions: private.
+#[cfg(any(target_os = "espidf", test))]
+mod debouncer;
+#[cfg(target_os = "espidf")]
+pub(crate) use debouncer::Debouncer;
+
+// Typed attribute values: public.
+mod gatt_value;
+pub use gatt_value::GattValue;
+
+// Bluetooth SIG value formats: public.
+pub mod formats;
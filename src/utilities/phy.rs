@@ -0,0 +1,47 @@
+/// Represents a Bluetooth LE physical layer.
+///
+/// The values match the ones used by the HCI commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Phy {
+    /// The LE 1M PHY, supported by all devices.
+    #[default]
+    Le1M = 1,
+    /// The LE 2M PHY, which doubles the throughput at the expense of range.
+    Le2M = 2,
+    /// The LE Coded PHY, which increases the range at the expense of throughput.
+    LeCoded = 3,
+}
+
+#[cfg_attr(not(esp_idf_bt_ble_50_features_supported), allow(dead_code))]
+impl Phy {
+    /// Returns the bit that represents this PHY in the HCI preference masks.
+    pub(crate) const fn preference_mask(self) -> u8 {
+        1 << (self as u8 - 1)
+    }
+
+    /// Converts a PHY value reported by the controller.
+    pub(crate) const fn from_hci(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Le1M),
+            2 => Some(Self::Le2M),
+            3 => Some(Self::LeCoded),
+            _ => None,
+        }
+    }
+}
+
+impl From<Phy> for u8 {
+    fn from(phy: Phy) -> Self {
+        phy as Self
+    }
+}
+
+impl std::fmt::Display for Phy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Le1M => write!(f, "LE 1M"),
+            Self::Le2M => write!(f, "LE 2M"),
+            Self::LeCoded => write!(f, "LE Coded"),
+        }
+    }
+}
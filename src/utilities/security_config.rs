@@ -0,0 +1,255 @@
+use esp_idf_sys::*;
+
+/// Represents the input and output capabilities of the device, used to select the pairing method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IoCapabilities {
+    /// The device can display a passkey, but has no input.
+    DisplayOnly,
+    /// The device can display a value, and the user can answer yes or no.
+    DisplayYesNo,
+    /// The device has a keyboard, but no display.
+    KeyboardOnly,
+    /// The device has no input and no output. Pairing uses the "Just Works" method, without MITM protection.
+    #[default]
+    NoInputNoOutput,
+    /// The device has both a keyboard and a display.
+    KeyboardDisplay,
+}
+
+impl From<IoCapabilities> for esp_ble_io_cap_t {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from(capabilities: IoCapabilities) -> Self {
+        (match capabilities {
+            IoCapabilities::DisplayOnly => ESP_IO_CAP_OUT,
+            IoCapabilities::DisplayYesNo => ESP_IO_CAP_IO,
+            IoCapabilities::KeyboardOnly => ESP_IO_CAP_IN,
+            IoCapabilities::NoInputNoOutput => ESP_IO_CAP_NONE,
+            IoCapabilities::KeyboardDisplay => ESP_IO_CAP_KBDISP,
+        }) as Self
+    }
+}
+
+/// Represents the set of keys distributed during pairing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyDistribution {
+    encryption: bool,
+    identity: bool,
+    signing: bool,
+    link: bool,
+}
+
+impl KeyDistribution {
+    /// Creates a new, empty [`KeyDistribution`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Distributes the long term key, used to encrypt the link on reconnection.
+    #[must_use]
+    pub const fn encryption(mut self) -> Self {
+        self.encryption = true;
+        self
+    }
+
+    /// Distributes the identity resolving key and the identity address, used to resolve private addresses.
+    #[must_use]
+    pub const fn identity(mut self) -> Self {
+        self.identity = true;
+        self
+    }
+
+    /// Distributes the connection signature resolving key, used for signed writes.
+    #[must_use]
+    pub const fn signing(mut self) -> Self {
+        self.signing = true;
+        self
+    }
+
+    /// Derives the BR/EDR link key from the LE keys.
+    #[must_use]
+    pub const fn link(mut self) -> Self {
+        self.link = true;
+        self
+    }
+}
+
+impl From<KeyDistribution> for u8 {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from(keys: KeyDistribution) -> Self {
+        let mut result = 0;
+
+        if keys.encryption {
+            result |= ESP_BLE_ENC_KEY_MASK;
+        }
+
+        if keys.identity {
+            result |= ESP_BLE_ID_KEY_MASK;
+        }
+
+        if keys.signing {
+            result |= ESP_BLE_CSR_KEY_MASK;
+        }
+
+        if keys.link {
+            result |= ESP_BLE_LINK_KEY_MASK;
+        }
+
+        result as Self
+    }
+}
+
+/// Represents the configuration of the Security Manager, used when pairing with a client.
+///
+/// This is applied to the [`GattServer`] with the [`GattServer::security`] method.
+///
+/// The default configuration enables bonding and LE Secure Connections,
+/// without MITM protection and without input or output capabilities.
+///
+/// [`GattServer`]: crate::gatt_server::GattServer
+/// [`GattServer::security`]: crate::gatt_server::GattServer::security
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityConfig {
+    pub(crate) bonding: bool,
+    pub(crate) mitm: bool,
+    pub(crate) secure_connections: bool,
+    pub(crate) secure_connections_only: bool,
+    pub(crate) io_capabilities: IoCapabilities,
+    pub(crate) min_key_size: u8,
+    pub(crate) max_key_size: u8,
+    pub(crate) initiator_keys: KeyDistribution,
+    pub(crate) responder_keys: KeyDistribution,
+    pub(crate) static_passkey: Option<u32>,
+    pub(crate) oob: bool,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            bonding: true,
+            mitm: false,
+            secure_connections: true,
+            secure_connections_only: false,
+            io_capabilities: IoCapabilities::NoInputNoOutput,
+            min_key_size: 7,
+            max_key_size: 16,
+            initiator_keys: KeyDistribution::new().encryption().identity(),
+            responder_keys: KeyDistribution::new().encryption().identity(),
+            static_passkey: None,
+            oob: false,
+        }
+    }
+}
+
+impl SecurityConfig {
+    /// Creates a new [`SecurityConfig`] with the default values.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the keys exchanged during pairing are stored, so that the peer can reconnect without pairing again.
+    #[must_use]
+    pub const fn bonding(mut self, bonding: bool) -> Self {
+        self.bonding = bonding;
+        self
+    }
+
+    /// Requires protection against man-in-the-middle attacks.
+    ///
+    /// This requires [`IoCapabilities`] that allow the user to confirm or enter a value.
+    #[must_use]
+    pub const fn mitm(mut self) -> Self {
+        self.mitm = true;
+        self
+    }
+
+    /// Sets whether LE Secure Connections are supported.
+    #[must_use]
+    pub const fn secure_connections(mut self, secure_connections: bool) -> Self {
+        self.secure_connections = secure_connections;
+        self
+    }
+
+    /// Rejects the peers that do not support LE Secure Connections, or that do not meet the requested requirements.
+    #[must_use]
+    pub const fn secure_connections_only(mut self) -> Self {
+        self.secure_connections = true;
+        self.secure_connections_only = true;
+        self
+    }
+
+    /// Sets the input and output capabilities of the device.
+    #[must_use]
+    pub const fn io_capabilities(mut self, capabilities: IoCapabilities) -> Self {
+        self.io_capabilities = capabilities;
+        self
+    }
+
+    /// Sets the minimum and maximum encryption key size, in bytes.
+    ///
+    /// The sizes are clamped between 7 and 16 bytes.
+    #[must_use]
+    pub fn key_size(mut self, min: u8, max: u8) -> Self {
+        self.min_key_size = min.clamp(7, 16);
+        self.max_key_size = max.clamp(self.min_key_size, 16);
+        self
+    }
+
+    /// Sets the keys that the initiator (the client) distributes during pairing.
+    #[must_use]
+    pub const fn initiator_keys(mut self, keys: KeyDistribution) -> Self {
+        self.initiator_keys = keys;
+        self
+    }
+
+    /// Sets the keys that the responder (this device) distributes during pairing.
+    #[must_use]
+    pub const fn responder_keys(mut self, keys: KeyDistribution) -> Self {
+        self.responder_keys = keys;
+        self
+    }
+
+    /// Uses a fixed passkey, between 0 and 999999, instead of a random one.
+    ///
+    /// This is useful for devices with no display, where the passkey is printed on a label.
+    /// Larger values are clamped to 999999.
+    #[must_use]
+    pub fn static_passkey(mut self, passkey: u32) -> Self {
+        self.static_passkey = Some(passkey.min(999_999));
+        self
+    }
+
+    /// Enables pairing with data exchanged out of band, for example through NFC or a QR code.
+    ///
+    /// The data is exchanged with the callbacks set with [`GattServer::on_oob_request`]
+    /// and [`GattServer::on_sc_oob_request`].
+    ///
+    /// [`GattServer::on_oob_request`]: crate::gatt_server::GattServer::on_oob_request
+    /// [`GattServer::on_sc_oob_request`]: crate::gatt_server::GattServer::on_sc_oob_request
+    #[must_use]
+    pub const fn oob(mut self) -> Self {
+        self.oob = true;
+        self
+    }
+
+    /// Returns the authentication requirements, as expected by the Bluetooth stack.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn authentication_requirements(&self) -> esp_ble_auth_req_t {
+        let mut result = 0;
+
+        if self.bonding {
+            result |= ESP_LE_AUTH_BOND;
+        }
+
+        if self.mitm {
+            result |= ESP_LE_AUTH_REQ_MITM;
+        }
+
+        if self.secure_connections {
+            result |= ESP_LE_AUTH_REQ_SC_ONLY;
+        }
+
+        result as esp_ble_auth_req_t
+    }
+}
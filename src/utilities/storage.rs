@@ -0,0 +1,314 @@
+// Only `NvsStorage` depends on ESP-IDF, so that the other backends can be used in tests on the host.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+#[cfg(target_os = "espidf")]
+use esp_idf_svc::nvs::{
+    EspCustomNvsPartition, EspDefaultNvsPartition, EspNvs, NvsCustom, NvsDefault, NvsPartitionId,
+};
+#[cfg(target_os = "espidf")]
+use esp_idf_sys::EspError;
+
+/// The maximum length of a storage key, as required by NVS.
+#[cfg(any(target_os = "espidf", test))]
+pub(crate) const MAX_KEY_LENGTH: usize = 15;
+
+/// Returns `true` if the key can be used with every [`Storage`].
+#[cfg(any(target_os = "espidf", test))]
+pub(crate) fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LENGTH
+}
+
+/// An error returned by a [`Storage`].
+#[derive(Debug)]
+pub enum StorageError {
+    /// The non-volatile storage failed.
+    #[cfg(target_os = "espidf")]
+    Nvs(EspError),
+    /// The filesystem failed.
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(target_os = "espidf")]
+            Self::Nvs(error) => write!(f, "NVS error: {error}"),
+            Self::Io(error) => write!(f, "I/O error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+#[cfg(target_os = "espidf")]
+impl From<EspError> for StorageError {
+    fn from(error: EspError) -> Self {
+        Self::Nvs(error)
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// A key-value store, used to persist the state of the server across reboots.
+///
+/// The library uses it to store CCCD subscriptions, persisted characteristic values and
+/// the random static address. Keys are at most 15 characters long, as required by NVS.
+///
+/// The storage is set with [`GattServer::storage`].
+///
+/// [`GattServer::storage`]: crate::gatt_server::GattServer::storage
+pub trait Storage: Send {
+    /// Returns the value stored under `key`, or `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be read.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Stores `value` under `key`, replacing any previous value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be written.
+    fn set(&mut self, key: &str, value: &[u8]) -> Result<(), StorageError>;
+
+    /// Removes the value stored under `key`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be removed.
+    fn remove(&mut self, key: &str) -> Result<(), StorageError>;
+}
+
+#[cfg(target_os = "espidf")]
+/// A [`Storage`] backed by a namespace of a non-volatile storage partition.
+pub struct NvsStorage<T: NvsPartitionId> {
+    nvs: EspNvs<T>,
+}
+
+#[cfg(target_os = "espidf")]
+impl NvsStorage<NvsDefault> {
+    /// Opens a namespace of the default NVS partition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the partition is not declared, has already been taken, or cannot be opened.
+    pub fn new(namespace: &str) -> Result<Self, EspError> {
+        Ok(Self {
+            nvs: EspNvs::new(EspDefaultNvsPartition::take()?, namespace, true)?,
+        })
+    }
+
+    /// Opens a namespace of an already taken default NVS partition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the namespace cannot be opened.
+    pub fn with_partition(
+        partition: EspDefaultNvsPartition,
+        namespace: &str,
+    ) -> Result<Self, EspError> {
+        Ok(Self {
+            nvs: EspNvs::new(partition, namespace, true)?,
+        })
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl NvsStorage<NvsCustom> {
+    /// Opens a namespace of the NVS partition with the given label.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the partition is not declared or cannot be opened.
+    pub fn custom(partition: &str, namespace: &str) -> Result<Self, EspError> {
+        Ok(Self {
+            nvs: EspNvs::new(EspCustomNvsPartition::take(partition)?, namespace, true)?,
+        })
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl<T: NvsPartitionId> Storage for NvsStorage<T> {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let Some(length) = self.nvs.len(key)? else {
+            return Ok(None);
+        };
+
+        let mut buf = vec![0u8; length];
+        Ok(self.nvs.get_raw(key, &mut buf)?.map(<[u8]>::to_vec))
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        self.nvs.set_raw(key, value)?;
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        self.nvs.remove(key)?;
+        Ok(())
+    }
+}
+
+/// A [`Storage`] that keeps the values in RAM, and loses them on reboot.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    values: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    /// Creates a new, empty [`MemoryStorage`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        self.values.insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        self.values.remove(key);
+        Ok(())
+    }
+}
+
+/// A [`Storage`] that keeps each value in a file of a directory.
+///
+/// This is mainly useful for tests on the host, or with a mounted filesystem.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    directory: PathBuf,
+}
+
+impl FileStorage {
+    /// Creates a new [`FileStorage`] in the given directory, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, StorageError> {
+        let directory = directory.as_ref().to_path_buf();
+        std::fs::create_dir_all(&directory)?;
+
+        Ok(Self { directory })
+    }
+
+    fn read(&self, key: &str) -> std::io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.directory.join(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Writes a value to a temporary file, then renames it, so that a value is never partially written.
+    fn write(&self, key: &str, value: &[u8]) -> std::io::Result<()> {
+        let temporary = self.directory.join(format!(".{key}.tmp"));
+
+        let path = self.directory.join(key);
+
+        std::fs::write(&temporary, value)?;
+
+        match std::fs::rename(&temporary, &path) {
+            // FAT filesystems cannot replace a file by renaming another one.
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+                std::fs::remove_file(&path)?;
+                std::fs::rename(&temporary, &path)
+            }
+            result => result,
+        }
+    }
+
+    fn delete(&self, key: &str) -> std::io::Result<()> {
+        match std::fs::remove_file(self.directory.join(key)) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.read(key)?)
+    }
+
+    fn set(&mut self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        Ok(self.write(key, value)?)
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        Ok(self.delete(key)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_length_limit() {
+        assert!(!is_valid_key(""));
+        assert!(is_valid_key("a"));
+        assert!(is_valid_key("fifteen_chars_k"));
+        assert!(!is_valid_key("sixteen_chars_ke"));
+    }
+
+    fn storage(name: &str) -> FileStorage {
+        let directory =
+            std::env::temp_dir().join(format!("bluedroid-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        FileStorage::new(directory).unwrap()
+    }
+
+    #[test]
+    fn missing_values() {
+        let mut storage = storage("missing");
+
+        assert_eq!(storage.get("key").unwrap(), None);
+        assert!(storage.remove("key").is_ok());
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let mut storage = storage("round-trip");
+
+        storage.set("key", &[1, 2, 3]).unwrap();
+        assert_eq!(storage.get("key").unwrap(), Some(vec![1, 2, 3]));
+
+        storage.set("key", &[4]).unwrap();
+        assert_eq!(storage.get("key").unwrap(), Some(vec![4]));
+
+        storage.remove("key").unwrap();
+        assert_eq!(storage.get("key").unwrap(), None);
+    }
+
+    #[test]
+    fn writes_leave_no_temporary_file() {
+        let mut storage = storage("temporary");
+
+        storage.set("key", &[1, 2, 3]).unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&storage.directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, ["key"]);
+    }
+}
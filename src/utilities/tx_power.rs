@@ -0,0 +1,180 @@
+use esp_idf_sys::*;
+
+/// Represents a transmit power level supported by the Bluetooth controller.
+///
+/// The ESP32 supports levels between -12 dBm and +9 dBm,
+/// while the ESP32-C3 and ESP32-S3 support levels between -24 dBm and +21 dBm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TxPowerLevel {
+    /// -24 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    N24,
+    /// -21 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    N21,
+    /// -18 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    N18,
+    /// -15 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    N15,
+    /// -12 dBm.
+    N12,
+    /// -9 dBm.
+    N9,
+    /// -6 dBm.
+    N6,
+    /// -3 dBm.
+    N3,
+    /// 0 dBm.
+    N0,
+    /// +3 dBm.
+    P3,
+    /// +6 dBm.
+    P6,
+    /// +9 dBm.
+    P9,
+    /// +12 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    P12,
+    /// +15 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    P15,
+    /// +18 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    P18,
+    /// +21 dBm.
+    #[cfg(any(esp32c3, esp32s3))]
+    P21,
+}
+
+impl TxPowerLevel {
+    /// Returns the transmit power in dBm.
+    #[must_use]
+    pub const fn dbm(self) -> i8 {
+        match self {
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::N24 => -24,
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::N21 => -21,
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::N18 => -18,
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::N15 => -15,
+            Self::N12 => -12,
+            Self::N9 => -9,
+            Self::N6 => -6,
+            Self::N3 => -3,
+            Self::N0 => 0,
+            Self::P3 => 3,
+            Self::P6 => 6,
+            Self::P9 => 9,
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::P12 => 12,
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::P15 => 15,
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::P18 => 18,
+            #[cfg(any(esp32c3, esp32s3))]
+            Self::P21 => 21,
+        }
+    }
+
+    /// Returns the level currently configured in the controller for the given power type.
+    #[must_use]
+    pub fn current(power_type: TxPowerType) -> Option<Self> {
+        let level = unsafe { esp_ble_tx_power_get(power_type.into()) };
+
+        #[allow(non_upper_case_globals)]
+        match level {
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_N24 => Some(Self::N24),
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_N21 => Some(Self::N21),
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_N18 => Some(Self::N18),
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_N15 => Some(Self::N15),
+            esp_power_level_t_ESP_PWR_LVL_N12 => Some(Self::N12),
+            esp_power_level_t_ESP_PWR_LVL_N9 => Some(Self::N9),
+            esp_power_level_t_ESP_PWR_LVL_N6 => Some(Self::N6),
+            esp_power_level_t_ESP_PWR_LVL_N3 => Some(Self::N3),
+            esp_power_level_t_ESP_PWR_LVL_N0 => Some(Self::N0),
+            esp_power_level_t_ESP_PWR_LVL_P3 => Some(Self::P3),
+            esp_power_level_t_ESP_PWR_LVL_P6 => Some(Self::P6),
+            esp_power_level_t_ESP_PWR_LVL_P9 => Some(Self::P9),
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_P12 => Some(Self::P12),
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_P15 => Some(Self::P15),
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_P18 => Some(Self::P18),
+            #[cfg(any(esp32c3, esp32s3))]
+            esp_power_level_t_ESP_PWR_LVL_P21 => Some(Self::P21),
+            _ => None,
+        }
+    }
+}
+
+impl From<TxPowerLevel> for esp_power_level_t {
+    fn from(level: TxPowerLevel) -> Self {
+        match level {
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::N24 => esp_power_level_t_ESP_PWR_LVL_N24,
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::N21 => esp_power_level_t_ESP_PWR_LVL_N21,
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::N18 => esp_power_level_t_ESP_PWR_LVL_N18,
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::N15 => esp_power_level_t_ESP_PWR_LVL_N15,
+            TxPowerLevel::N12 => esp_power_level_t_ESP_PWR_LVL_N12,
+            TxPowerLevel::N9 => esp_power_level_t_ESP_PWR_LVL_N9,
+            TxPowerLevel::N6 => esp_power_level_t_ESP_PWR_LVL_N6,
+            TxPowerLevel::N3 => esp_power_level_t_ESP_PWR_LVL_N3,
+            TxPowerLevel::N0 => esp_power_level_t_ESP_PWR_LVL_N0,
+            TxPowerLevel::P3 => esp_power_level_t_ESP_PWR_LVL_P3,
+            TxPowerLevel::P6 => esp_power_level_t_ESP_PWR_LVL_P6,
+            TxPowerLevel::P9 => esp_power_level_t_ESP_PWR_LVL_P9,
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::P12 => esp_power_level_t_ESP_PWR_LVL_P12,
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::P15 => esp_power_level_t_ESP_PWR_LVL_P15,
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::P18 => esp_power_level_t_ESP_PWR_LVL_P18,
+            #[cfg(any(esp32c3, esp32s3))]
+            TxPowerLevel::P21 => esp_power_level_t_ESP_PWR_LVL_P21,
+        }
+    }
+}
+
+impl std::fmt::Display for TxPowerLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+} dBm", self.dbm())
+    }
+}
+
+/// Represents the activity that a transmit power level applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxPowerType {
+    /// The power used for advertising.
+    Advertising,
+    /// The power used for scanning.
+    Scanning,
+    /// The power used for all the activities without a specific setting.
+    Default,
+    /// The power used for the connection with the given controller connection handle, from 0 to 8.
+    Connection(u8),
+}
+
+impl From<TxPowerType> for esp_ble_power_type_t {
+    fn from(power_type: TxPowerType) -> Self {
+        match power_type {
+            TxPowerType::Advertising => esp_ble_power_type_t_ESP_BLE_PWR_TYPE_ADV,
+            TxPowerType::Scanning => esp_ble_power_type_t_ESP_BLE_PWR_TYPE_SCAN,
+            TxPowerType::Default => esp_ble_power_type_t_ESP_BLE_PWR_TYPE_DEFAULT,
+            TxPowerType::Connection(handle) => {
+                esp_ble_power_type_t_ESP_BLE_PWR_TYPE_CONN_HDL0 + Self::from(handle.min(8))
+            }
+        }
+    }
+}